@@ -0,0 +1,59 @@
+//! Selects the nightly const trait syntax understood by the compiler.
+//!
+//! The syntax has changed several times while `const_trait_impl` has been
+//! unstable. The compiler's commit date picks a profile, which is exposed to
+//! the macro as `cfg(c0nst_profile = "...")`. A nightly compiler also sets
+//! `cfg(c0nst_nightly_rustc)`, which only the UI tests use, since their
+//! diagnostics differ between channels.
+
+use std::env;
+use std::process::Command;
+
+/// Profiles paired with the first commit date which no longer accepts them.
+///
+/// Compilers with a later (or unknown) date get the `current` profile.
+const PROFILES: &[(&str, &str)] = &[
+    // `#[const_trait] trait` with `~const` bounds
+    ("tilde", "2025-06-28"),
+    // `#[const_trait] trait` with `[const]` bounds
+    ("bracket", "2025-07-18"),
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+    println!(
+        "cargo:rustc-check-cfg=cfg(c0nst_profile, values(\"tilde\", \"bracket\", \"current\"))"
+    );
+    println!("cargo:rustc-check-cfg=cfg(c0nst_nightly_rustc)");
+
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let version = Command::new(rustc)
+        .arg("-vV")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        .unwrap_or_default();
+
+    // Dates are `YYYY-MM-DD`, so they compare correctly as strings.
+    let date = version
+        .lines()
+        .find_map(|line| line.strip_prefix("commit-date: "))
+        .map(str::trim)
+        .filter(|date| date.starts_with(|c: char| c.is_ascii_digit()));
+
+    let profile = PROFILES
+        .iter()
+        .find(|(_, end)| date.map_or(false, |date| date < *end))
+        .map_or("current", |(profile, _)| *profile);
+
+    println!("cargo:rustc-cfg=c0nst_profile=\"{}\"", profile);
+
+    let release = version
+        .lines()
+        .find_map(|line| line.strip_prefix("release: "))
+        .unwrap_or_default();
+
+    if release.contains("-nightly") || release.contains("-dev") {
+        println!("cargo:rustc-cfg=c0nst_nightly_rustc");
+    }
+}
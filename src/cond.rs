@@ -0,0 +1,144 @@
+//! `cond_kw!`, which emits a keyword only under a `cfg` predicate
+//!
+//! ```text
+//! c0nst::cond_kw! {
+//!     a5ync => async if feature = "async";
+//!
+//!     pub a5ync fn fetch() -> u8 { 1 }
+//! }
+//! ```
+//!
+//! A proc macro cannot evaluate the `cfg` of the crate invoking it, so every
+//! item using the placeholder is emitted twice: with the keyword under
+//! `#[cfg(predicate)]` and without it under `#[cfg(not(predicate))]`.
+
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use crate::convert::{apply_keyword, items, Keyword, State};
+use crate::error::Error;
+
+/// The parsed `placeholder => keyword if predicate;` header
+struct Header {
+    placeholder: Ident,
+    keyword: Ident,
+    predicate: TokenStream,
+}
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let tokens: Vec<_> = input.into_iter().collect();
+    let (header, body) = match header(&tokens) {
+        Ok(parsed) => parsed,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    let placeholder = header.placeholder.to_string();
+    let keyword = header.keyword.to_string();
+    let keyword = Keyword {
+        placeholder: &placeholder,
+        keyword: &keyword,
+    };
+
+    let mut output = TokenStream::new();
+    for item in items(body) {
+        if !mentions(item, &placeholder) {
+            output.extend(item.iter().cloned());
+            continue;
+        }
+
+        let span = header.placeholder.span();
+        for (state, predicate) in [
+            (State::Enabled, header.predicate.clone()),
+            (State::Disabled, not(header.predicate.clone(), span)),
+        ] {
+            output.extend(cfg(predicate, span));
+            output.extend(apply_keyword(
+                item.iter().cloned().collect(),
+                &keyword,
+                state,
+            ));
+        }
+    }
+
+    output
+}
+
+/// Parses the leading `placeholder => keyword if predicate;`.
+fn header(tokens: &[TokenTree]) -> Result<(Header, &[TokenTree]), Error> {
+    let expected = |span: Span| {
+        Error::new(
+            span,
+            "expected `placeholder => keyword if predicate;`, such as \
+             `a5ync => async if feature = \"async\";`",
+        )
+    };
+
+    let semi = tokens
+        .iter()
+        .position(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == ';'));
+
+    let (header, body) = match semi {
+        Some(semi) => (&tokens[..semi], &tokens[semi + 1..]),
+        None => return Err(expected(Span::call_site())),
+    };
+
+    match header {
+        [TokenTree::Ident(placeholder), TokenTree::Punct(eq), TokenTree::Punct(gt), TokenTree::Ident(keyword), TokenTree::Ident(cond), predicate @ ..]
+            if eq.as_char() == '='
+                && gt.as_char() == '>'
+                && cond == "if"
+                && !predicate.is_empty() =>
+        {
+            if placeholder == keyword {
+                return Err(Error::new(
+                    placeholder.span(),
+                    "the placeholder must differ from the keyword",
+                ));
+            }
+
+            let header = Header {
+                placeholder: placeholder.clone(),
+                keyword: keyword.clone(),
+                predicate: predicate.iter().cloned().collect(),
+            };
+
+            Ok((header, body))
+        }
+        [first, ..] => Err(expected(first.span())),
+        [] => Err(expected(Span::call_site())),
+    }
+}
+
+/// Returns whether the tokens contain the identifier `name`.
+fn mentions(tokens: &[TokenTree], name: &str) -> bool {
+    tokens.iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => ident == name,
+        TokenTree::Group(group) => mentions(&group.stream().into_iter().collect::<Vec<_>>(), name),
+        _ => false,
+    })
+}
+
+/// Creates `not(predicate)`.
+fn not(predicate: TokenStream, span: Span) -> TokenStream {
+    let mut group = Group::new(Delimiter::Parenthesis, predicate);
+    group.set_span(span);
+    [TokenTree::Ident(Ident::new("not", span)), group.into()]
+        .into_iter()
+        .collect()
+}
+
+/// Creates the attribute `#[cfg(predicate)]`.
+fn cfg(predicate: TokenStream, span: Span) -> TokenStream {
+    let mut args = Group::new(Delimiter::Parenthesis, predicate);
+    args.set_span(span);
+
+    let inner: TokenStream = [TokenTree::Ident(Ident::new("cfg", span)), args.into()]
+        .into_iter()
+        .collect();
+    let mut attr = Group::new(Delimiter::Bracket, inner);
+    attr.set_span(span);
+
+    let mut pound = Punct::new('#', Spacing::Alone);
+    pound.set_span(span);
+
+    [TokenTree::Punct(pound), attr.into()].into_iter().collect()
+}
@@ -0,0 +1,209 @@
+use proc_macro2::{Delimiter, TokenTree};
+
+use super::item::{self, generics, qualifiers, Scope};
+use super::token::{
+    is_bracketed, is_fn_marker, is_fn_start, is_ident, is_marker, is_maybe_const, is_punct, KEYWORD,
+};
+use super::Context;
+use crate::error::Error;
+
+/// Reports unsupported uses of `c0nst` in a single level of tokens.
+///
+/// Checks run on the input before any rewriting, so they see `c0nst` exactly
+/// as the user wrote it.
+pub fn check(tokens: &[TokenTree], cx: &mut Context) {
+    for (index, token) in tokens.iter().enumerate() {
+        if is_ident(token, "dyn") {
+            trait_object(&tokens[index + 1..], cx);
+        }
+
+        if is_fn_marker(tokens, index) && !pointer(tokens, index, cx) {
+            method(token, cx);
+        }
+
+        if is_ident(token, "fn") {
+            literal_const(tokens, index, cx);
+        }
+
+        if let Some(suggestion) = nightly_syntax(tokens, index) {
+            cx.errors.push(Error::new(token.span(), suggestion));
+        }
+    }
+}
+
+/// Const trait syntax written with the real keyword compiles on nightly only.
+///
+/// Returns the error suggesting the `c0nst` form.
+fn nightly_syntax(tokens: &[TokenTree], index: usize) -> Option<&'static str> {
+    let token = &tokens[index];
+    let prev = index.checked_sub(1).map(|i| &tokens[i]);
+    let next = |offset: usize| tokens.get(index + offset);
+
+    match token {
+        TokenTree::Group(g) if is_maybe_const(g) => {
+            return Some("`[const]` only compiles on nightly: did you mean `[c0nst]`?");
+        }
+        TokenTree::Group(g) if is_bracketed(g, "const_trait") => {
+            return Some("`#[const_trait]` only compiles on nightly: did you mean `c0nst trait`?");
+        }
+        _ => {}
+    }
+
+    if is_punct(token, '~') && next(1).map_or(false, |tt| is_ident(tt, "const")) {
+        return Some("`~const` only compiles on nightly: did you mean `[c0nst]`?");
+    }
+
+    if !is_ident(token, "const") {
+        return None;
+    }
+
+    let is_trait = |tt: Option<&TokenTree>| tt.map_or(false, |tt| is_ident(tt, "trait"));
+    if is_trait(next(1))
+        || (next(1).map_or(false, |tt| is_ident(tt, "unsafe")) && is_trait(next(2)))
+    {
+        return Some("`const trait` only compiles on nightly: did you mean `c0nst trait`?");
+    }
+
+    // `impl const Trait` and `impl<T> const Trait`
+    let start = item::start(tokens, index);
+    let keyword = (start..index).rfind(|&i| is_ident(&tokens[i], "impl"));
+    if let Some(keyword) = keyword {
+        let (params, _) = generics(&tokens[keyword + 1..]);
+        if keyword + 1 + params.len() == index {
+            return Some("`impl const` only compiles on nightly: did you mean `impl c0nst`?");
+        }
+    }
+
+    // `T: const Trait` and `T: Clone + const Trait`
+    let bound = prev.map_or(false, |tt| is_punct(tt, ':') || is_punct(tt, '+'))
+        && matches!(next(1), Some(TokenTree::Ident(..)));
+
+    match bound {
+        true => Some("`const` bounds only compile on nightly: did you mean `c0nst`?"),
+        false => None,
+    }
+}
+
+/// A `const fn` stays const on stable, where its `[c0nst]` bounds are
+/// removed, so calls through them only fail on stable.
+fn literal_const(tokens: &[TokenTree], keyword: usize, cx: &mut Context) {
+    let constant = match qualifiers(tokens, keyword) {
+        Some(start) => tokens[start..keyword]
+            .iter()
+            .any(|tt| is_ident(tt, "const")),
+        None => false,
+    };
+
+    if !constant {
+        return;
+    }
+
+    let header = tokens[keyword + 1..].iter().take_while(|tt| match tt {
+        TokenTree::Group(g) => g.delimiter() != Delimiter::Brace,
+        tt => !is_punct(tt, ';'),
+    });
+
+    if let Some(marker) = header.filter_map(find_marker).next() {
+        let written = match is_ident(&marker, KEYWORD) {
+            true => "c0nst",
+            false => "[c0nst]",
+        };
+
+        cx.errors.push(Error::new(
+            marker.span(),
+            format!(
+                "`{written}` bound on a `const fn`, which stays const on stable where the \
+                 bound is removed: use `c0nst fn` instead"
+            ),
+        ));
+    }
+}
+
+/// Returns the first marker in the token or inside its groups.
+fn find_marker(token: &TokenTree) -> Option<TokenTree> {
+    match token {
+        tt if is_marker(tt) => Some(tt.clone()),
+        TokenTree::Group(group) => group.stream().into_iter().find_map(|tt| find_marker(&tt)),
+        _ => None,
+    }
+}
+
+/// Trait methods are const through their trait rather than on their own.
+fn method(marker: &TokenTree, cx: &mut Context) {
+    let message = match cx.scope {
+        Scope::Trait { c0nst: false } => "`c0nst fn` in a trait requires a `c0nst trait`",
+        Scope::Impl {
+            c0nst: false,
+            inherent: false,
+            ..
+        } => "`c0nst fn` in a trait impl requires `impl c0nst Trait`",
+        Scope::Extern => {
+            "`c0nst fn` in an `extern` block is not supported: foreign functions cannot be const"
+        }
+        _ => return,
+    };
+
+    cx.errors.push(Error::new(marker.span(), message));
+}
+
+/// Function pointer types cannot be const, even on nightly.
+///
+/// Returns whether the marker at `index` was reported.
+fn pointer(tokens: &[TokenTree], index: usize, cx: &mut Context) -> bool {
+    // Skip `unsafe`, `extern "C"` and the like to find the `fn` keyword.
+    let keyword = tokens[index + 1..]
+        .iter()
+        .position(|tt| is_ident(tt, "fn") || !(is_fn_start(tt) || is_literal(tt)))
+        .map(|offset| index + 1 + offset)
+        .filter(|&keyword| is_ident(&tokens[keyword], "fn"));
+
+    let keyword = match keyword {
+        Some(keyword) => keyword,
+        None => return false,
+    };
+
+    // Unlike a function item, a pointer type has no name, which also tells
+    // them apart inside a tuple or another group.
+    let item = qualifiers(tokens, keyword).is_some()
+        && tokens
+            .get(keyword + 1)
+            .map_or(false, |tt| matches!(tt, TokenTree::Ident(..)));
+    if item {
+        return false;
+    }
+
+    cx.errors.push(Error::new(
+        tokens[index].span(),
+        "`c0nst fn` pointer types are not supported: function pointers cannot be const",
+    ));
+    true
+}
+
+fn is_literal(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Literal(..))
+}
+
+/// Trait objects cannot have const bounds, even on nightly.
+fn trait_object(bounds: &[TokenTree], cx: &mut Context) {
+    let mut depth = 0usize;
+
+    for (index, token) in bounds.iter().enumerate() {
+        let arrow = index > 0 && is_punct(&bounds[index - 1], '-');
+
+        match token {
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            TokenTree::Punct(p) if p.as_char() == '>' && !arrow => match depth {
+                0 => return,
+                _ => depth -= 1,
+            },
+            TokenTree::Punct(p) if depth == 0 && matches!(p.as_char(), ',' | ';' | '=') => return,
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => return,
+            TokenTree::Ident(i) if i == "impl" || i == "where" => return,
+            tt if is_marker(tt) => cx.errors.push(Error::new(
+                tt.span(),
+                format!("`dyn {tt}` is not supported: trait objects cannot have const bounds"),
+            )),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Qualified and aliased paths to `Destruct`
+//!
+//! The built-in rules only match the bare `Destruct` after a marker. On
+//! stable, `[c0nst] core::marker::Destruct` and `[c0nst] D` after
+//! `use core::marker::Destruct as D;` are shortened to `[c0nst] Destruct` so
+//! the rules remove them, and the `use` itself is removed, since the trait is
+//! unstable. Nightly keeps the paths as written.
+
+use proc_macro2::{Ident, TokenTree};
+
+use super::item;
+use super::token::{is_ident, is_marker, is_punct};
+use super::{Context, Target};
+
+/// The crates which export `marker::Destruct`
+const CRATES: &[&str] = &["core", "std"];
+
+/// Records the aliases of `Destruct` declared at this level and, on stable,
+/// removes their `use` items.
+pub fn aliases(tokens: &mut Vec<TokenTree>, cx: &mut Context) {
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let end = item::end(tokens, index);
+        if let Some(alias) = import(&tokens[index..end]) {
+            cx.destruct.extend(alias);
+            if cx.target == Target::Stable {
+                tokens.drain(index..end);
+                continue;
+            }
+        }
+
+        index = end;
+    }
+}
+
+/// Shortens the paths to `Destruct` after each marker at this level.
+pub fn normalize(tokens: &mut Vec<TokenTree>, cx: &Context) {
+    if cx.target != Target::Stable {
+        return;
+    }
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let len = match is_marker(&tokens[index]) {
+            true => path(&tokens[index + 1..], &cx.destruct),
+            false => 0,
+        };
+
+        if len > 0 {
+            let span = tokens[index + len].span();
+            let ident = TokenTree::Ident(Ident::new("Destruct", span));
+            tokens.splice(index + 1..index + 1 + len, std::iter::once(ident));
+        }
+
+        index += 1;
+    }
+}
+
+/// Returns whether the item is `use core::marker::Destruct;`, with the alias
+/// it declares, if any.
+fn import(item: &[TokenTree]) -> Option<Option<Ident>> {
+    let item = match item {
+        [vis, rest @ ..] if is_ident(vis, "pub") => match rest {
+            [TokenTree::Group(..), rest @ ..] => rest,
+            _ => rest,
+        },
+        _ => item,
+    };
+
+    let (path, alias) = match item {
+        [keyword, rest @ .., semi] if is_ident(keyword, "use") && is_punct(semi, ';') => match rest
+        {
+            [path @ .., r#as, TokenTree::Ident(alias)] if is_ident(r#as, "as") => {
+                (path, Some(alias.clone()))
+            }
+            _ => (rest, None),
+        },
+        _ => return None,
+    };
+
+    match !path.is_empty() && path.len() == full(path) {
+        true => Some(alias),
+        false => None,
+    }
+}
+
+/// Returns the number of tokens of the path to `Destruct` at the start of
+/// `tokens`: `Destruct` through one of `aliases`, or a path like
+/// `::core::marker::Destruct`.
+fn path(tokens: &[TokenTree], aliases: &[Ident]) -> usize {
+    match tokens {
+        [TokenTree::Ident(alias), rest @ ..] if aliases.contains(alias) => match rest {
+            [colon, ..] if is_punct(colon, ':') => 0,
+            _ => 1,
+        },
+        _ => full(tokens),
+    }
+}
+
+/// Returns the number of tokens of `core::marker::Destruct` or
+/// `std::marker::Destruct` at the start of `tokens`, with an optional leading
+/// `::`, or 0.
+fn full(tokens: &[TokenTree]) -> usize {
+    let (offset, rest) = match tokens {
+        [a, b, rest @ ..] if is_punct(a, ':') && is_punct(b, ':') => (2, rest),
+        _ => (0, tokens),
+    };
+
+    match rest {
+        [krate, a, b, marker, c, d, name, ..]
+            if CRATES.iter().any(|k| is_ident(krate, k))
+                && is_punct(a, ':')
+                && is_punct(b, ':')
+                && is_ident(marker, "marker")
+                && is_punct(c, ':')
+                && is_punct(d, ':')
+                && is_ident(name, "Destruct") =>
+        {
+            offset + 7
+        }
+        _ => 0,
+    }
+}
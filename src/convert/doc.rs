@@ -0,0 +1,199 @@
+use proc_macro2::{Delimiter, Group, Literal, TokenTree};
+
+use super::{Profile, Target};
+
+/// Rewrites `c0nst` in the body of a `#[doc = "..."]` attribute.
+///
+/// Returns `None` if the group is not a doc attribute or needs no changes.
+pub fn rewrite(attr: &Group, target: Target, profile: Profile) -> Option<Group> {
+    if attr.delimiter() != Delimiter::Bracket {
+        return None;
+    }
+
+    let mut tokens: Vec<_> = attr.stream().into_iter().collect();
+    let literal = match tokens.as_slice() {
+        [TokenTree::Ident(name), TokenTree::Punct(eq), TokenTree::Literal(literal)]
+            if name == "doc" && eq.as_char() == '=' =>
+        {
+            literal
+        }
+        _ => return None,
+    };
+
+    let text = replace(&unquote(&literal.to_string())?, target, profile)?;
+    let mut replacement = Literal::string(&text);
+    replacement.set_span(literal.span());
+    tokens[2] = replacement.into();
+
+    let mut group = Group::new(Delimiter::Bracket, tokens.into_iter().collect());
+    group.set_span(attr.span());
+    Some(group)
+}
+
+/// Replaces every standalone `c0nst` or `[c0nst]` word in the text.
+///
+/// Paths, macro calls and compounds such as `c0nst::c0nst!` or
+/// `c0nst-compatible` are left untouched. On stable, the clause around a code
+/// span which only held the word, or around the word ending a clause, is
+/// removed as well, since it no longer says anything.
+fn replace(text: &str, target: Target, profile: Profile) -> Option<String> {
+    const WORD: &str = "c0nst";
+
+    let mut output = String::with_capacity(text.len());
+    let mut changed = false;
+    let mut rest = text;
+
+    while let Some(index) = rest.find(WORD) {
+        output.push_str(&rest[..index]);
+        rest = &rest[index + WORD.len()..];
+
+        let prev = output.chars().next_back();
+        let next = rest.chars().next();
+        let joined = |c: char, extra: &str| c.is_alphanumeric() || c == '_' || extra.contains(c);
+        if prev.map_or(false, |c| joined(c, ":-")) || next.map_or(false, |c| joined(c, ":!(-")) {
+            output.push_str(WORD);
+            continue;
+        }
+
+        changed = true;
+        let bracketed = output.ends_with('[') && rest.starts_with(']');
+        match target {
+            Target::Nightly if bracketed && profile.tilde() => {
+                output.pop();
+                output.push_str("~const");
+                rest = &rest[1..];
+            }
+            Target::Nightly => output.push_str("const"),
+            Target::Stable => {
+                if bracketed {
+                    output.pop();
+                    rest = &rest[1..];
+                }
+
+                let span = output.ends_with('`') && rest.starts_with('`');
+                if span {
+                    output.pop();
+                    rest = &rest[1..];
+                }
+
+                let after = rest.trim_start_matches(' ');
+                if span || after.is_empty() || after.starts_with(|c: char| DELIMITERS.contains(c)) {
+                    output.push(REMOVED);
+                } else {
+                    rest = rest.strip_prefix(' ').unwrap_or(rest);
+                }
+            }
+        }
+    }
+
+    output.push_str(rest);
+    changed.then(|| drop_clauses(output))
+}
+
+/// Characters which end a clause in prose
+const DELIMITERS: &str = ".,;:!?()";
+
+/// Stands for a removed word whose clause is dropped
+const REMOVED: char = '\0';
+
+/// Drops each clause containing `REMOVED`, together with one of the
+/// delimiters around it.
+fn drop_clauses(mut text: String) -> String {
+    while let Some(mark) = text.find(REMOVED) {
+        let delimiters = delimiters(&text);
+        let start = delimiters.iter().rev().find(|&&i| i < mark).map(|&i| i + 1);
+        let end = delimiters.iter().find(|&&i| i > mark).copied();
+
+        let prev = start.map(|i| text[..i].chars().next_back().unwrap_or(' '));
+        let next = end.map(|i| text[i..].chars().next().unwrap_or(' '));
+        let start = start.unwrap_or(0);
+        let end = end.unwrap_or(text.len());
+
+        // Leading whitespace separates the clause from the previous one.
+        let clause = &text[start..end];
+        let body = start + clause.len() - clause.trim_start().len();
+        let capital = clause
+            .chars()
+            .find(|c| c.is_alphanumeric())
+            .map_or(false, char::is_uppercase);
+        let sentence = prev.map_or(true, |c| ".!?".contains(c));
+
+        let range = match (prev, next) {
+            (Some(p), _) if ",;:".contains(p) => start - 1..end,
+            (Some('('), Some(')')) => {
+                let open = text[..start - 1].trim_end().len();
+                open..end + 1
+            }
+            (_, Some(n)) if sentence && ",;:.!?".contains(n) => {
+                let after = &text[end + 1..];
+                body..end + 1 + after.len() - after.trim_start().len()
+            }
+            _ => body..end,
+        };
+
+        text.replace_range(range.clone(), "");
+        if range.start == text.len() {
+            text.truncate(text.trim_end().len());
+        } else if capital && sentence {
+            let rest = &text[range.start..];
+            let upper: String = rest.chars().take(1).flat_map(char::to_uppercase).collect();
+            let first = rest.chars().next().map_or(0, char::len_utf8);
+            text.replace_range(range.start..range.start + first, &upper);
+        }
+    }
+
+    text
+}
+
+/// Returns the positions of the delimiters outside of code spans.
+fn delimiters(text: &str) -> Vec<usize> {
+    let mut code = false;
+
+    text.char_indices()
+        .filter(|&(_, c)| {
+            code ^= c == '`';
+            !code && DELIMITERS.contains(c)
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Decodes the value of a string literal from its source representation.
+pub fn unquote(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        let body = raw.get(hashes..raw.len() - hashes)?;
+        return Some(body.strip_prefix('"')?.strip_suffix('"')?.to_string());
+    }
+
+    let body = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut output = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next()? {
+            'n' => output.push('\n'),
+            'r' => output.push('\r'),
+            't' => output.push('\t'),
+            '0' => output.push('\0'),
+            c @ ('\\' | '\'' | '"') => output.push(c),
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                output.push(char::from(u8::from_str_radix(&hex, 16).ok()?));
+            }
+            'u' => {
+                let hex: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                output.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+            }
+            '\n' => while chars.next_if(|c| c.is_whitespace()).is_some() {},
+            _ => return None,
+        }
+    }
+
+    Some(output)
+}
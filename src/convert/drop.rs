@@ -0,0 +1,350 @@
+//! `#[c0nst_drop]`, which derives the bounds of a const `Drop` impl
+//!
+//! On nightly, `impl<T> const Drop for Guard<T>` only compiles if every field
+//! of `Guard<T>` can be dropped in a const context, so the impl needs a
+//! `[const] Destruct` bound for each field type using a type parameter.
+
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use super::item::generics;
+use super::token::{is_ident, is_punct, split, KEYWORD};
+use super::{Context, Target};
+use crate::error::Error;
+
+/// The name of the attribute
+const ATTRIBUTE: &str = "c0nst_drop";
+
+/// Removes each `#[c0nst_drop]` at this level and adds the `[c0nst] Destruct`
+/// bounds of its type to the matching `impl c0nst Drop`.
+pub fn derive(tokens: &mut Vec<TokenTree>, cx: &mut Context) {
+    let mut index = 0;
+
+    while index + 1 < tokens.len() {
+        if !is_punct(&tokens[index], '#') || !is_attribute(&tokens[index + 1]) {
+            index += 1;
+            continue;
+        }
+
+        let span = tokens[index + 1].span();
+        tokens.drain(index..index + 2);
+
+        let (name, params, types) = match fields(&tokens[index..]) {
+            Some(item) => item,
+            None => {
+                cx.errors.push(Error::new(
+                    span,
+                    "`#[c0nst_drop]` must be placed on a struct, enum or union",
+                ));
+                continue;
+            }
+        };
+
+        let (keyword, body, args) = match drop_impl(tokens, &name) {
+            Some(found) => found,
+            None => {
+                cx.errors.push(Error::new(
+                    span,
+                    format!("`#[c0nst_drop]` requires an `impl c0nst Drop for {name}`"),
+                ));
+                continue;
+            }
+        };
+
+        // The bounds are only needed by the const impl.
+        if cx.target == Target::Nightly && !types.is_empty() {
+            let clause = (keyword..body).find(|&i| is_ident(&tokens[i], "where"));
+            // The impl may name the parameters differently, such as
+            // `impl<U> c0nst Drop for Guard<U>`, so they are matched by position.
+            let names: Vec<_> = params.iter().zip(&args).collect();
+            let types = types.iter().map(|ty| substitute(ty, &names)).collect();
+            let bounds = bounds(types, clause.is_none(), span);
+            let position = match clause {
+                Some(..) if !is_punct(&tokens[body - 1], ',') => {
+                    tokens.insert(body, punct(',', span));
+                    body + 1
+                }
+                _ => body,
+            };
+
+            tokens.splice(position..position, bounds);
+        }
+    }
+}
+
+/// Returns whether the token is the body of `#[c0nst_drop]`.
+fn is_attribute(token: &TokenTree) -> bool {
+    match token {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+            let mut inner = group.stream().into_iter();
+            matches!((inner.next(), inner.next()), (Some(tt), None) if is_ident(&tt, ATTRIBUTE))
+        }
+        _ => false,
+    }
+}
+
+/// A generic parameter of the type
+struct Param {
+    name: Ident,
+    lifetime: bool,
+}
+
+/// Returns the name of the type defined by the item, its generic parameters
+/// and its field types which use a type parameter.
+fn fields(item: &[TokenTree]) -> Option<(Ident, Vec<Param>, Vec<Vec<TokenTree>>)> {
+    let (keyword, name, rest) = match skip_prefix(item) {
+        [keyword, TokenTree::Ident(name), rest @ ..]
+            if ["struct", "enum", "union"]
+                .iter()
+                .any(|kw| is_ident(keyword, kw)) =>
+        {
+            (keyword, name.clone(), rest)
+        }
+        _ => return None,
+    };
+
+    let (generics, rest) = generics(rest);
+    let params = type_params(generics);
+
+    // A tuple struct has its fields right after the generics, and any other
+    // body follows the where clause.
+    let body = match rest.first() {
+        Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Parenthesis => Some(g),
+        _ => rest.iter().find_map(|tt| match tt {
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => Some(g),
+            _ => None,
+        }),
+    };
+
+    let mut types = Vec::new();
+    match body {
+        Some(body) if is_ident(keyword, "enum") => {
+            let variants: Vec<_> = body.stream().into_iter().collect();
+            for variant in split(&variants) {
+                let fields = variant.iter().find_map(|tt| match tt {
+                    TokenTree::Group(g) if g.delimiter() != Delimiter::Bracket => Some(g),
+                    _ => None,
+                });
+
+                if let Some(fields) = fields {
+                    field_types(fields, &mut types);
+                }
+            }
+        }
+        Some(body) => field_types(body, &mut types),
+        None => {}
+    }
+
+    let mut seen = Vec::new();
+    types.retain(|ty| {
+        let key = ty.iter().cloned().collect::<TokenStream>().to_string();
+        let new = uses(ty, &params) && !seen.contains(&key);
+        seen.push(key);
+        new
+    });
+
+    Some((name, all_params(generics), types))
+}
+
+/// Collects the types of the named (`{ a: T }`) or unnamed (`(T)`) fields.
+fn field_types(fields: &Group, types: &mut Vec<Vec<TokenTree>>) {
+    let tokens: Vec<_> = fields.stream().into_iter().collect();
+
+    for field in split(&tokens) {
+        let mut field = skip_prefix(field);
+        if fields.delimiter() == Delimiter::Brace {
+            field = match field.iter().position(|tt| is_punct(tt, ':')) {
+                Some(colon) => &field[colon + 1..],
+                None => continue,
+            };
+        }
+
+        types.push(field.to_vec());
+    }
+}
+
+/// Skips the attributes and visibility of an item or a field.
+fn skip_prefix(mut tokens: &[TokenTree]) -> &[TokenTree] {
+    loop {
+        tokens = match tokens {
+            [pound, TokenTree::Group(_), rest @ ..] if is_punct(pound, '#') => rest,
+            [vis, TokenTree::Group(g), rest @ ..] if is_ident(vis, "pub") && is_restriction(g) => {
+                rest
+            }
+            [vis, rest @ ..] if is_ident(vis, "pub") => rest,
+            _ => return tokens,
+        };
+    }
+}
+
+/// Returns whether the group is the restriction of `pub(crate)` and the like.
+fn is_restriction(group: &Group) -> bool {
+    let first = group.stream().into_iter().next();
+    group.delimiter() == Delimiter::Parenthesis
+        && first.map_or(false, |tt| {
+            ["crate", "self", "super", "in"]
+                .iter()
+                .any(|kw| is_ident(&tt, kw))
+        })
+}
+
+/// Returns the tokens between the brackets of `<...>`.
+fn inner(generics: &[TokenTree]) -> &[TokenTree] {
+    generics
+        .get(1..generics.len().saturating_sub(1))
+        .unwrap_or_default()
+}
+
+/// Returns the names of the type parameters in `<...>`.
+fn type_params(params: &[TokenTree]) -> Vec<Ident> {
+    split(inner(params))
+        .into_iter()
+        .filter_map(|param| match param {
+            [TokenTree::Ident(name), ..] if name != "const" => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns every parameter in `<...>`, in order.
+fn all_params(params: &[TokenTree]) -> Vec<Param> {
+    split(inner(params))
+        .into_iter()
+        .filter_map(|param| match param {
+            [TokenTree::Punct(p), TokenTree::Ident(name), ..] if p.as_char() == '\'' => {
+                Some(Param {
+                    name: name.clone(),
+                    lifetime: true,
+                })
+            }
+            [TokenTree::Ident(kw), TokenTree::Ident(name), ..] if kw == "const" => Some(Param {
+                name: name.clone(),
+                lifetime: false,
+            }),
+            [TokenTree::Ident(name), ..] => Some(Param {
+                name: name.clone(),
+                lifetime: false,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Replaces the parameters of the type with the matching generic arguments.
+fn substitute(ty: &[TokenTree], args: &[(&Param, &Vec<TokenTree>)]) -> Vec<TokenTree> {
+    let mut output = Vec::new();
+
+    for (index, tt) in ty.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| &ty[i]);
+        let lifetime = previous.map_or(false, |tt| is_punct(tt, '\''));
+        let path = previous.map_or(false, |tt| is_punct(tt, ':'));
+
+        let arg = match tt {
+            TokenTree::Ident(ident) if !path => args
+                .iter()
+                .find(|(param, _)| param.lifetime == lifetime && param.name == *ident),
+            TokenTree::Group(group) => {
+                let stream: Vec<_> = group.stream().into_iter().collect();
+                let mut group = Group::new(
+                    group.delimiter(),
+                    substitute(&stream, args).into_iter().collect(),
+                );
+                group.set_span(tt.span());
+                output.push(group.into());
+                continue;
+            }
+            _ => None,
+        };
+
+        match arg {
+            Some((_, arg)) => {
+                // The arguments of a lifetime include its `'`.
+                if lifetime {
+                    output.pop();
+                }
+                output.extend(arg.iter().cloned());
+            }
+            None => output.push(tt.clone()),
+        }
+    }
+
+    output
+}
+
+/// Returns whether the type mentions one of the parameters.
+fn uses(ty: &[TokenTree], params: &[Ident]) -> bool {
+    ty.iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => params.contains(ident),
+        TokenTree::Group(group) => uses(&group.stream().into_iter().collect::<Vec<_>>(), params),
+        _ => false,
+    })
+}
+
+/// Finds `impl c0nst Drop for Name<...>`, where `Name` may also be a path
+/// such as `m::Name`, and returns the positions of `Drop` and of the impl body
+/// with the generic arguments of `Name`.
+fn drop_impl(tokens: &[TokenTree], name: &Ident) -> Option<(usize, usize, Vec<Vec<TokenTree>>)> {
+    (1..tokens.len().saturating_sub(2)).find_map(|index| {
+        if !is_ident(&tokens[index - 1], KEYWORD)
+            || !is_ident(&tokens[index], "Drop")
+            || !is_ident(&tokens[index + 1], "for")
+        {
+            return None;
+        }
+
+        let (args, _) = generics(self_type(&tokens[index + 2..], name)?);
+        let args = split(inner(args)).into_iter().map(<[_]>::to_vec).collect();
+
+        tokens[index..]
+            .iter()
+            .position(|tt| matches!(tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace))
+            .map(|offset| (index, index + offset, args))
+    })
+}
+
+/// Skips the path to `name` at the start of the tokens and returns the tokens
+/// after it.
+fn self_type<'a>(mut tokens: &'a [TokenTree], name: &Ident) -> Option<&'a [TokenTree]> {
+    loop {
+        tokens = match tokens {
+            [first, second, rest @ ..] if is_punct(first, ':') && is_punct(second, ':') => rest,
+            [TokenTree::Ident(_), first, second, rest @ ..]
+                if is_punct(first, ':') && is_punct(second, ':') =>
+            {
+                rest
+            }
+            [TokenTree::Ident(ident), rest @ ..] if ident == name => return Some(rest),
+            _ => return None,
+        };
+    }
+}
+
+/// Creates the predicates `Type: [c0nst] Destruct` located at `span`.
+fn bounds(types: Vec<Vec<TokenTree>>, clause: bool, span: Span) -> Vec<TokenTree> {
+    let mut bounds = Vec::new();
+    if clause {
+        bounds.push(Ident::new("where", span).into());
+    }
+
+    for (index, ty) in types.into_iter().enumerate() {
+        if index > 0 {
+            bounds.push(punct(',', span));
+        }
+
+        let keyword = TokenTree::Ident(Ident::new(KEYWORD, span));
+        let mut marker = Group::new(Delimiter::Bracket, keyword.into());
+        marker.set_span(span);
+
+        bounds.extend(ty);
+        bounds.push(punct(':', span));
+        bounds.push(marker.into());
+        bounds.push(Ident::new("Destruct", span).into());
+    }
+
+    bounds
+}
+
+fn punct(ch: char, span: Span) -> TokenTree {
+    let mut punct = Punct::new(ch, Spacing::Alone);
+    punct.set_span(span);
+    punct.into()
+}
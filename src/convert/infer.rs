@@ -0,0 +1,224 @@
+//! `#![c0nst(infer_bounds)]`, which marks the bounds of conditionally const
+//! items as `[c0nst]`
+//!
+//! Within the header of a `c0nst fn` or an `impl c0nst Trait`, every trait
+//! bound on a generic parameter or in the where clause becomes `[c0nst]`,
+//! unless it is already marked, opts out with `?c0nst Trait`, or names a
+//! well-known trait which is rarely const, such as `Copy` or `Iterator`.
+
+use std::ops::Range;
+
+use proc_macro2::{Delimiter, Group, Ident, TokenStream, TokenTree};
+
+use super::item::{self, generics};
+use super::redundant::PRIMITIVES;
+use super::token::{is_fn_marker, is_ident, is_marker, is_punct, KEYWORD};
+use super::Context;
+use crate::error::Error;
+
+/// Traits which are never const
+const MARKER_TRAITS: &[&str] = &["Sized", "Copy", "Send", "Sync", "Unpin"];
+
+/// Standard traits whose bounds are almost always meant to be non-const
+const STD_TRAITS: &[&str] = &[
+    "Any",
+    "Debug",
+    "DoubleEndedIterator",
+    "Display",
+    "Error",
+    "ExactSizeIterator",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "Future",
+    "Hash",
+    "Hasher",
+    "IntoIterator",
+    "Iterator",
+];
+
+/// Marks the bounds of the items at this level.
+pub fn infer(tokens: &mut Vec<TokenTree>) {
+    let mut positions = Vec::new();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let end = item::end(tokens, index);
+        positions.extend(bounds(tokens, index, end));
+        index = end;
+    }
+
+    for position in positions.into_iter().rev() {
+        let span = tokens[position].span();
+        let keyword = TokenTree::Ident(Ident::new(KEYWORD, span));
+        let mut marker = Group::new(Delimiter::Bracket, TokenStream::from(keyword));
+        marker.set_span(span);
+        tokens.insert(position, marker.into());
+    }
+}
+
+/// Removes each `?c0nst` at this level, which is an error unless bounds are
+/// inferred.
+pub fn opt_out(tokens: &mut Vec<TokenTree>, cx: &mut Context) {
+    let mut index = 0;
+
+    while index + 1 < tokens.len() {
+        if !is_punct(&tokens[index], '?') || !is_ident(&tokens[index + 1], KEYWORD) {
+            index += 1;
+            continue;
+        }
+
+        if !cx.options.infer_bounds {
+            cx.errors.push(Error::new(
+                tokens[index + 1].span(),
+                "`?c0nst` requires `#![c0nst(infer_bounds)]`",
+            ));
+        }
+
+        tokens.drain(index..index + 2);
+    }
+}
+
+/// Returns the positions of the bounds to mark in the item at `start..end`.
+fn bounds(tokens: &[TokenTree], start: usize, end: usize) -> Vec<usize> {
+    // The generics follow the name of a `c0nst fn` or the `impl` keyword of
+    // an `impl c0nst Trait`.
+    let params = match (start..end).find(|&i| is_fn_marker(tokens, i)) {
+        Some(marker) => match (marker..end).find(|&i| is_ident(&tokens[i], "fn")) {
+            Some(keyword) if keyword + 2 <= end => keyword + 2,
+            _ => return Vec::new(),
+        },
+        None => match (start..end).find(|&i| is_ident(&tokens[i], "impl")) {
+            Some(keyword) => match generics(&tokens[keyword + 1..end]) {
+                (_, [first, ..]) if is_ident(first, KEYWORD) => keyword + 1,
+                _ => return Vec::new(),
+            },
+            None => return Vec::new(),
+        },
+    };
+
+    let (generic, _) = generics(&tokens[params..end]);
+    let mut positions = Vec::new();
+
+    // `<T: Bound, 'a: 'b, const N: usize>`
+    let inner = params + 1..params + generic.len().saturating_sub(1);
+    for param in ranges(tokens, inner, ',') {
+        let colon = match &tokens[param.clone()] {
+            [TokenTree::Ident(name), colon, ..] if name != "const" && is_punct(colon, ':') => {
+                param.start + 1
+            }
+            _ => continue,
+        };
+
+        let default = (colon..param.end).find(|&i| is_punct(&tokens[i], '='));
+        positions.extend(markable(tokens, colon + 1..default.unwrap_or(param.end)));
+    }
+
+    // `where Ty: Bound, ...` up to the body.
+    let header = params + generic.len()..end;
+    let clause = header.clone().find(|&i| is_ident(&tokens[i], "where"));
+    if let Some(clause) = clause {
+        let body = (clause..end)
+            .find(|&i| match &tokens[i] {
+                TokenTree::Group(g) => g.delimiter() == Delimiter::Brace,
+                tt => is_punct(tt, ';'),
+            })
+            .unwrap_or(end);
+
+        for predicate in ranges(tokens, clause + 1..body, ',') {
+            let colon = predicate.clone().find(|&i| {
+                is_punct(&tokens[i], ':')
+                    && !is_punct(&tokens[i - 1], ':')
+                    && !tokens.get(i + 1).map_or(false, |tt| is_punct(tt, ':'))
+            });
+
+            if let Some(colon) = colon {
+                if !is_concrete(&tokens[predicate.start..colon]) {
+                    positions.extend(markable(tokens, colon + 1..predicate.end));
+                }
+            }
+        }
+    }
+
+    positions
+}
+
+/// Returns the start of each bound in `range` which should be marked.
+fn markable(tokens: &[TokenTree], range: Range<usize>) -> Vec<usize> {
+    ranges(tokens, range, '+')
+        .filter(|bound| {
+            let bound = &tokens[bound.clone()];
+            let first = match bound.first() {
+                Some(first) => first,
+                None => return false,
+            };
+
+            // Lifetimes, `?Sized`, `?c0nst`, HRTBs and explicit modifiers.
+            let unmarkable = is_punct(first, '\'')
+                || is_punct(first, '?')
+                || is_punct(first, '~')
+                || is_ident(first, "for")
+                || is_ident(first, "const")
+                || is_marker(first)
+                || matches!(first, TokenTree::Group(..));
+
+            !unmarkable && !is_skipped(bound)
+        })
+        .map(|bound| bound.start)
+        .collect()
+}
+
+/// Returns whether the bound names one of `MARKER_TRAITS` or `STD_TRAITS`.
+fn is_skipped(bound: &[TokenTree]) -> bool {
+    let path = bound
+        .iter()
+        .position(|tt| is_punct(tt, '<') || matches!(tt, TokenTree::Group(..)))
+        .unwrap_or(bound.len());
+
+    bound[..path]
+        .iter()
+        .rev()
+        .find_map(|tt| match tt {
+            TokenTree::Ident(ident) => Some(ident),
+            _ => None,
+        })
+        .map_or(false, |name| {
+            MARKER_TRAITS.iter().chain(STD_TRAITS).any(|t| name == t)
+        })
+}
+
+/// Returns whether the bounded type is a lifetime or a primitive type, whose
+/// bounds have no const choice.
+fn is_concrete(ty: &[TokenTree]) -> bool {
+    match ty {
+        [first, ..] if is_punct(first, '\'') => true,
+        [TokenTree::Ident(ty)] => PRIMITIVES.iter().any(|p| ty == p),
+        _ => false,
+    }
+}
+
+/// Splits `range` on the separators outside of `<...>`.
+fn ranges(
+    tokens: &[TokenTree],
+    range: Range<usize>,
+    separator: char,
+) -> impl Iterator<Item = Range<usize>> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = range.start;
+
+    for index in range.clone() {
+        let tt = &tokens[index];
+        if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') && !is_punct(&tokens[index - 1], '-') {
+            depth = depth.saturating_sub(1);
+        } else if is_punct(tt, separator) && depth == 0 {
+            parts.push(start..index);
+            start = index + 1;
+        }
+    }
+
+    parts.push(start..range.end);
+    parts.into_iter().filter(|part| !part.is_empty())
+}
@@ -0,0 +1,80 @@
+use proc_macro2::{Delimiter, Ident, TokenTree};
+
+use super::item::{self, generics, Scope};
+use super::token::{is_ident, KEYWORD};
+use super::{redundant, Context};
+use crate::error::Error;
+
+/// Removes the marker of each inherent `impl c0nst Type` at this level.
+///
+/// Inherent impls have no const form, so the marker moves to their methods
+/// instead (see `propagate`). `scopes` holds the scope of each brace group.
+pub fn unmark(tokens: &mut Vec<TokenTree>, scopes: &[Scope]) {
+    let mut scopes = scopes.iter();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let body =
+            matches!(&tokens[index], TokenTree::Group(g) if g.delimiter() == Delimiter::Brace);
+        let scope = match body {
+            true => scopes.next(),
+            false => None,
+        };
+
+        if let Some(Scope::Impl {
+            c0nst: true,
+            inherent: true,
+            ..
+        }) = scope
+        {
+            let start = item::start(tokens, index);
+            let keyword = (start..index).find(|&i| is_ident(&tokens[i], "impl"));
+
+            if let Some(keyword) = keyword {
+                let (params, _) = generics(&tokens[keyword + 1..index]);
+                tokens.remove(keyword + 1 + params.len());
+                index -= 1;
+            }
+        }
+
+        index += 1;
+    }
+}
+
+/// Marks every method of an inherent `impl c0nst Type` as `c0nst fn`.
+pub fn propagate(tokens: &mut Vec<TokenTree>, cx: &mut Context) {
+    let mut index = 0;
+
+    while index < tokens.len() {
+        if !is_ident(&tokens[index], "fn") {
+            index += 1;
+            continue;
+        }
+
+        let start = match item::qualifiers(tokens, index) {
+            Some(start) => start,
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+
+        let qualifiers = &tokens[start..index];
+        if let Some(marker) = qualifiers.iter().find(|tt| is_ident(tt, KEYWORD)) {
+            redundant::method(marker.span(), "an `impl c0nst` block", cx);
+        } else if qualifiers.iter().any(|tt| is_ident(tt, "const")) {
+            // Already const.
+        } else if qualifiers.iter().any(|tt| is_ident(tt, "async")) {
+            cx.errors.push(Error::new(
+                tokens[index].span(),
+                "`async fn` cannot be const: move it out of the `impl c0nst` block",
+            ));
+        } else {
+            let marker = Ident::new(KEYWORD, tokens[index].span());
+            tokens.insert(start, marker.into());
+            index += 1;
+        }
+
+        index += 1;
+    }
+}
@@ -0,0 +1,275 @@
+use proc_macro2::{Delimiter, TokenTree};
+
+use super::token::{is_fn_start, is_ident, is_punct, KEYWORD};
+
+/// The kind of item whose body is being converted
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scope {
+    /// The top level of the invocation or the body of a module
+    #[default]
+    Module,
+
+    /// The body of a trait
+    Trait { c0nst: bool },
+
+    /// The body of an impl block
+    Impl {
+        c0nst: bool,
+        inherent: bool,
+        generic: bool,
+    },
+
+    /// The body of an `extern` block
+    Extern,
+
+    /// The fields of a struct or union, or the variants of an enum
+    Fields,
+
+    /// Any other group in braces, such as a function body or a block
+    Block,
+
+    /// Any other group, such as a tuple or the parameters of a function
+    Other,
+}
+
+impl Scope {
+    /// Returns whether this scope contains items.
+    pub fn has_items(self) -> bool {
+        !matches!(self, Self::Fields | Self::Block | Self::Other)
+    }
+
+    /// Returns the scope of the brace group at `index`.
+    pub fn of(tokens: &[TokenTree], index: usize) -> Self {
+        match tokens[index] {
+            TokenTree::Group(ref g) if g.delimiter() == Delimiter::Brace => {}
+            _ => return Self::Other,
+        }
+
+        match Self::header(&tokens[start(tokens, index)..index]) {
+            Self::Other => Self::Block,
+            scope => scope,
+        }
+    }
+
+    fn header(header: &[TokenTree]) -> Self {
+        const KEYWORDS: &[&str] = &[
+            "trait", "impl", "mod", "fn", "struct", "enum", "union", "extern",
+        ];
+
+        let keyword = header
+            .iter()
+            .position(|tt| KEYWORDS.iter().any(|kw| is_ident(tt, kw)));
+
+        let keyword = match keyword {
+            Some(index) => index,
+            None => return Self::Other,
+        };
+
+        // Only attributes, visibility and qualifiers come before the keyword,
+        // unlike in an expression such as `|| -> impl Trait { .. }`.
+        let prefix = header[..keyword].iter().all(|tt| match tt {
+            TokenTree::Punct(p) => p.as_char() == '#' || p.as_char() == '!',
+            TokenTree::Group(g) => g.delimiter() != Delimiter::Brace,
+            TokenTree::Ident(i) => [
+                "pub", "const", KEYWORD, "async", "unsafe", "auto", "default",
+            ]
+            .iter()
+            .any(|kw| i == kw),
+            TokenTree::Literal(..) => false,
+        });
+
+        if !prefix {
+            return Self::Other;
+        }
+
+        match &header[keyword] {
+            TokenTree::Ident(i) if i == "trait" => Self::Trait {
+                c0nst: header[..keyword].iter().any(|tt| is_ident(tt, KEYWORD)),
+            },
+            TokenTree::Ident(i) if i == "impl" => impl_header(&header[keyword + 1..]),
+            TokenTree::Ident(i) if i == "mod" => Self::Module,
+            TokenTree::Ident(i) if i == "struct" || i == "enum" || i == "union" => Self::Fields,
+            TokenTree::Ident(i) if i == "extern" && !header.iter().any(|tt| is_ident(tt, "fn")) => {
+                Self::Extern
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Returns the start of the item containing the token at `index`.
+///
+/// The item starts after the previous item, which ends with `;` or a body.
+pub fn start(tokens: &[TokenTree], index: usize) -> usize {
+    tokens[..index]
+        .iter()
+        .rposition(|tt| match tt {
+            TokenTree::Punct(p) => p.as_char() == ';',
+            TokenTree::Group(g) => g.delimiter() == Delimiter::Brace,
+            _ => false,
+        })
+        .map_or(0, |i| i + 1)
+}
+
+/// Returns the end of the item which starts at `index`.
+///
+/// An item ends after `;` or, unless it declares a `const`, `static`, `type`,
+/// `use` or `let`, after a body in braces. An unterminated item ends with the tokens.
+pub fn end(tokens: &[TokenTree], index: usize) -> usize {
+    tokens[index..]
+        .iter()
+        .enumerate()
+        .position(|(offset, tt)| match tt {
+            TokenTree::Punct(p) => p.as_char() == ';',
+            TokenTree::Group(g) => {
+                g.delimiter() == Delimiter::Brace && !is_declaration(&tokens[index..index + offset])
+            }
+            _ => false,
+        })
+        .map_or(tokens.len(), |offset| index + offset + 1)
+}
+
+/// Splits the tokens into items.
+pub fn items(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut items = Vec::new();
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let end = end(tokens, index);
+        items.push(&tokens[index..end]);
+        index = end;
+    }
+
+    items
+}
+
+/// Returns whether the item is a declaration ending with `;`, such as
+/// `const X: T = T { .. };`.
+fn is_declaration(item: &[TokenTree]) -> bool {
+    let mut idents = item
+        .iter()
+        .filter(|tt| matches!(tt, TokenTree::Ident(i) if i != "pub"));
+
+    match idents.next() {
+        Some(tt) if is_ident(tt, "const") => !idents.next().map_or(false, is_fn_start),
+        Some(tt) => ["static", "type", "use", "let"]
+            .iter()
+            .any(|kw| is_ident(tt, kw)),
+        None => false,
+    }
+}
+
+/// The qualifiers of a function, in the order rustc requires them
+const QUALIFIERS: &[&str] = &["const", KEYWORD, "async", "unsafe", "extern"];
+
+/// Returns where the qualifiers of the function at `index` begin.
+///
+/// `index` is the `fn` keyword. Returns `None` if it does not start a function
+/// item, such as the `fn` of a function pointer type.
+pub fn qualifiers(tokens: &[TokenTree], index: usize) -> Option<usize> {
+    let start = start(tokens, index);
+    let mut qualifiers = None;
+
+    for (offset, tt) in tokens[start..index].iter().enumerate() {
+        match tt {
+            // Attributes, visibility and the ABI of `extern "C"`.
+            TokenTree::Punct(p) if p.as_char() == '#' => {}
+            TokenTree::Group(g) if g.delimiter() != Delimiter::Brace => {}
+            TokenTree::Literal(..) => {}
+            TokenTree::Ident(i) if i == "pub" || i == "default" => {}
+
+            TokenTree::Ident(i) if QUALIFIERS.iter().any(|q| i == q) => {
+                qualifiers.get_or_insert(start + offset);
+            }
+
+            _ => return None,
+        }
+    }
+
+    Some(qualifiers.unwrap_or(index))
+}
+
+/// Sorts the qualifiers of each `c0nst fn` at this level into the order rustc
+/// requires, so `unsafe extern "C" c0nst fn` becomes
+/// `c0nst unsafe extern "C" fn`.
+pub fn order(tokens: &mut [TokenTree]) {
+    for index in 0..tokens.len() {
+        if !is_ident(&tokens[index], "fn") {
+            continue;
+        }
+
+        let start = match qualifiers(tokens, index) {
+            Some(start) => start,
+            None => continue,
+        };
+
+        // The ABI literal of `extern "C"` ranks right after `extern`.
+        let rank = |tt: &TokenTree| match tt {
+            TokenTree::Ident(i) => QUALIFIERS.iter().position(|q| i == q),
+            TokenTree::Literal(..) => Some(QUALIFIERS.len()),
+            _ => None,
+        };
+
+        let qualifiers = &mut tokens[start..index];
+        let marked = qualifiers.iter().any(|tt| is_ident(tt, KEYWORD));
+        if marked && qualifiers.iter().all(|tt| rank(tt).is_some()) {
+            qualifiers.sort_by_key(rank);
+        }
+    }
+}
+
+/// Classifies the part of an impl header after the `impl` keyword.
+fn impl_header(header: &[TokenTree]) -> Scope {
+    let (params, rest) = generics(header);
+    let c0nst = rest.first().map_or(false, |tt| is_ident(tt, KEYWORD));
+
+    // A `for` which does not start a higher-ranked binder (`for<'a>`).
+    let inherent = !rest.iter().enumerate().any(|(index, tt)| {
+        is_ident(tt, "for") && !rest.get(index + 1).map_or(false, |tt| is_punct(tt, '<'))
+    });
+
+    Scope::Impl {
+        c0nst,
+        inherent,
+        generic: has_type_params(params),
+    }
+}
+
+/// Splits leading generic parameters (`<...>`) from the following tokens.
+pub fn generics(tokens: &[TokenTree]) -> (&[TokenTree], &[TokenTree]) {
+    if !tokens.first().map_or(false, |tt| is_punct(tt, '<')) {
+        return (&[], tokens);
+    }
+
+    let mut depth = 0usize;
+    for (index, tt) in tokens.iter().enumerate() {
+        if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') && !is_punct(&tokens[index - 1], '-') {
+            depth -= 1;
+            if depth == 0 {
+                return tokens.split_at(index + 1);
+            }
+        }
+    }
+
+    (tokens, &[])
+}
+
+/// Returns whether generic parameters (`<...>`) declare a type parameter.
+pub fn has_type_params(params: &[TokenTree]) -> bool {
+    let mut depth = 0usize;
+
+    params.iter().enumerate().any(|(index, tt)| {
+        let start =
+            depth == 1 && (is_punct(&params[index - 1], '<') || is_punct(&params[index - 1], ','));
+
+        if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') && !is_punct(&params[index - 1], '-') {
+            depth -= 1;
+        }
+
+        start && matches!(tt, TokenTree::Ident(i) if i != "const")
+    })
+}
@@ -0,0 +1,132 @@
+use proc_macro2::{Delimiter, Ident, TokenTree};
+
+use super::item::{generics, has_type_params, Scope};
+use super::token::{is_fn_marker, is_ident, is_marker, is_punct};
+
+/// Macros which expand to code allowed in a `const fn` on stable
+const MACROS: &[&str] = &[
+    "assert",
+    "debug_assert",
+    "matches",
+    "panic",
+    "todo",
+    "unimplemented",
+    "unreachable",
+];
+
+/// Turns `c0nst fn` into `const fn` where the function is also valid as a
+/// `const fn` on stable.
+///
+/// A function qualifies if it is a free function or a method of an inherent
+/// impl which is not `async`, neither its signature nor the impl has type
+/// parameters, `impl Trait` types or const bounds, and its body has no calls
+/// which may go through a trait.
+pub fn keep(tokens: &mut [TokenTree], scope: Scope) {
+    match scope {
+        Scope::Module => {}
+        Scope::Impl {
+            inherent: true,
+            generic: false,
+            ..
+        } => {}
+        _ => return,
+    }
+
+    for index in 0..tokens.len() {
+        if is_fn_marker(tokens, index) && qualifies(&tokens[index + 1..]) {
+            tokens[index] = Ident::new("const", tokens[index].span()).into();
+        }
+    }
+}
+
+/// Checks the function signature following the `c0nst` marker.
+fn qualifies(tokens: &[TokenTree]) -> bool {
+    let body = tokens.iter().position(|tt| match tt {
+        TokenTree::Group(g) => g.delimiter() == Delimiter::Brace,
+        TokenTree::Punct(p) => p.as_char() == ';',
+        _ => false,
+    });
+
+    let (signature, body) = match body {
+        Some(index) => tokens.split_at(index),
+        None => return false,
+    };
+
+    if !is_marker_free(signature) || signature.iter().any(|tt| is_ident(tt, "async")) {
+        return false;
+    }
+
+    let body = match &body[0] {
+        TokenTree::Group(group) => group.stream().into_iter().collect(),
+        _ => Vec::new(),
+    };
+
+    if !is_const_body(&body) {
+        return false;
+    }
+
+    // Skip the qualifiers and name to find the generic parameters.
+    let name = match signature.iter().position(|tt| is_ident(tt, "fn")) {
+        Some(index) => index + 2,
+        None => return false,
+    };
+
+    let (params, _) = generics(signature.get(name..).unwrap_or_default());
+    !has_type_params(params)
+}
+
+/// Returns whether the body is free of constructs which are not const on
+/// stable or may call a trait method.
+///
+/// Any method call such as `x.clone()` and any call of an associated function
+/// such as `T::default()` may go through a trait, so they are rejected along
+/// with `for` loops, `?`, `async` and macros other than `assert!` and the like.
+fn is_const_body(tokens: &[TokenTree]) -> bool {
+    tokens.iter().enumerate().all(|(index, tt)| {
+        let prev = index.checked_sub(1).map(|i| &tokens[i]);
+        let next = |offset: usize| tokens.get(index + offset);
+        let call = next(1).map_or(
+            false,
+            |tt| matches!(tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis),
+        );
+        let turbofish = next(1).map_or(false, |tt| is_punct(tt, ':'))
+            && next(3).map_or(false, |tt| is_punct(tt, '<'));
+
+        match tt {
+            TokenTree::Group(group) => {
+                is_const_body(&group.stream().into_iter().collect::<Vec<_>>())
+            }
+
+            // `for x in iter { ... }`, but not `for<'a>`
+            TokenTree::Ident(i) if i == "for" => next(1).map_or(false, |tt| is_punct(tt, '<')),
+            TokenTree::Ident(i) if i == "async" || i == "await" => false,
+            TokenTree::Punct(p) if p.as_char() == '?' => false,
+
+            // `x.f(...)` and `T::f(...)`, with or without a turbofish
+            TokenTree::Ident(..)
+                if prev.map_or(false, |tt| is_punct(tt, '.') || is_punct(tt, ':')) =>
+            {
+                !call && !turbofish
+            }
+
+            // `name!(...)`, but not `x != y`
+            TokenTree::Ident(i)
+                if next(1).map_or(false, |tt| is_punct(tt, '!'))
+                    && matches!(next(2), Some(TokenTree::Group(..))) =>
+            {
+                MACROS.iter().any(|name| i == name)
+            }
+
+            _ => true,
+        }
+    })
+}
+
+/// Returns whether the tokens contain no `c0nst`, `[c0nst]` or `impl Trait`.
+fn is_marker_free(tokens: &[TokenTree]) -> bool {
+    tokens.iter().all(|tt| match tt {
+        tt if is_marker(tt) || is_ident(tt, "impl") => false,
+        TokenTree::Group(g) => is_marker_free(&g.stream().into_iter().collect::<Vec<_>>()),
+        _ => true,
+    })
+}
@@ -0,0 +1,309 @@
+//! Lints enabled by `#![c0nst(lint)]`
+//!
+//! They catch code which compiles on stable, where `c0nst` is removed, but
+//! fails once `c0nst` becomes `const` on nightly: trait calls without a
+//! `[c0nst]` bound, and constructs which are not allowed in a const fn.
+
+use proc_macro2::{Delimiter, Ident, Spacing, TokenTree};
+
+use super::item::{self, generics, qualifiers, Scope};
+use super::token::{is_fn_marker, is_ident, is_marker, is_punct, split, KEYWORD};
+use super::Context;
+use crate::warning::Warning;
+
+/// Iterator methods, which are not const
+const ITERATORS: &[&str] = &["iter", "iter_mut", "into_iter"];
+
+/// Macros which allocate or print, which is not const
+const MACROS: &[&str] = &["vec", "format", "print", "println", "eprint", "eprintln"];
+
+/// Runs the lints on the items of one level.
+pub fn lint(tokens: &[TokenTree], cx: &mut Context) {
+    // Methods of a `c0nst trait` or an `impl c0nst` are const through it.
+    let methods = matches!(
+        cx.scope,
+        Scope::Trait { c0nst: true } | Scope::Impl { c0nst: true, .. }
+    );
+
+    for index in 0..tokens.len() {
+        if is_fn_marker(tokens, index) {
+            bounds(&tokens[index + 1..], cx);
+        }
+
+        if !is_ident(&tokens[index], "fn") {
+            continue;
+        }
+
+        let c0nst = qualifiers(tokens, index).map_or(false, |start| {
+            methods || tokens[start..index].iter().any(|tt| is_ident(tt, KEYWORD))
+        });
+
+        let body = tokens[index..]
+            .iter()
+            .take_while(|tt| !is_punct(tt, ';'))
+            .find_map(|tt| match tt {
+                TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => Some(g),
+                _ => None,
+            });
+
+        if let (true, Some(body)) = (c0nst, body) {
+            let body: Vec<_> = body.stream().into_iter().collect();
+            constructs(&body, cx);
+        }
+    }
+}
+
+/// A type parameter of a `c0nst fn`
+struct Param {
+    name: Ident,
+    c0nst: bool,
+    used: bool,
+}
+
+/// Warns about type parameters used through trait calls in the body of a
+/// `c0nst fn` without a `c0nst` or `[c0nst]` bound.
+fn bounds(tokens: &[TokenTree], cx: &mut Context) {
+    let name = match tokens.iter().position(|tt| is_ident(tt, "fn")) {
+        Some(index) => index + 2,
+        None => return,
+    };
+
+    let (params, rest) = generics(tokens.get(name..).unwrap_or_default());
+    let (args, rest) = match rest.split_first() {
+        Some((TokenTree::Group(g), rest)) if g.delimiter() == Delimiter::Parenthesis => (g, rest),
+        _ => return,
+    };
+
+    let (body, signature) = match rest.iter().position(is_body) {
+        Some(index) => (&rest[index], &rest[..index]),
+        None => return,
+    };
+
+    let inner = params
+        .get(1..params.len().saturating_sub(1))
+        .unwrap_or_default();
+    let mut params: Vec<Param> = split(inner)
+        .into_iter()
+        .filter_map(|param| match param {
+            [TokenTree::Ident(name), rest @ ..] if name != "const" => Some(Param {
+                name: name.clone(),
+                c0nst: rest.iter().any(is_marker),
+                used: false,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    // Bounds in the where clause: `T: [c0nst] Trait`.
+    if let Some(clause) = signature.iter().position(|tt| is_ident(tt, "where")) {
+        for predicate in split(&signature[clause + 1..]) {
+            if let [TokenTree::Ident(name), colon, rest @ ..] = predicate {
+                let single = rest.first().map_or(true, |tt| !is_punct(tt, ':'));
+                if is_punct(colon, ':') && single && rest.iter().any(is_marker) {
+                    params
+                        .iter_mut()
+                        .filter(|p| p.name == *name)
+                        .for_each(|p| p.c0nst = true);
+                }
+            }
+        }
+    }
+
+    // Arguments whose type is a parameter: `x: T`, `x: &T` or `x: &mut T`.
+    let args: Vec<_> = args.stream().into_iter().collect();
+    let bindings: Vec<(Ident, Ident)> = split(&args)
+        .into_iter()
+        .filter_map(|arg| {
+            let colon = arg.iter().position(|tt| is_punct(tt, ':'))?;
+            let binding = match &arg[..colon] {
+                [TokenTree::Ident(b)] => b,
+                [mutability, TokenTree::Ident(b)] if is_ident(mutability, "mut") => b,
+                _ => return None,
+            };
+
+            let mut ty: Vec<_> = arg[colon + 1..]
+                .iter()
+                .filter(|tt| !is_punct(tt, '&') && !is_ident(tt, "mut"))
+                .collect();
+
+            // The lifetime of `&'a T`.
+            if ty.first().map_or(false, |tt| is_punct(tt, '\'')) {
+                ty.drain(..2.min(ty.len()));
+            }
+
+            match ty.as_slice() {
+                [TokenTree::Ident(ty)] => Some((binding.clone(), ty.clone())),
+                _ => None,
+            }
+        })
+        .collect();
+
+    if let TokenTree::Group(body) = body {
+        let body: Vec<_> = body.stream().into_iter().collect();
+        calls(&body, &mut params, &bindings, cx);
+    }
+}
+
+/// Finds trait calls through the parameters: `T::f()`, `<T as Trait>::f()`
+/// and `x.f()` where `x` is an argument of type `T`.
+fn calls(
+    tokens: &[TokenTree],
+    params: &mut [Param],
+    bindings: &[(Ident, Ident)],
+    cx: &mut Context,
+) {
+    for (index, token) in tokens.iter().enumerate() {
+        let next = |offset: usize| tokens.get(index + offset);
+
+        let used = match token {
+            TokenTree::Group(group) => {
+                let inner: Vec<_> = group.stream().into_iter().collect();
+                calls(&inner, params, bindings, cx);
+                continue;
+            }
+
+            // `T::f(...)`
+            TokenTree::Ident(name) if is_assoc_call(tokens, index) => Some(name.clone()),
+
+            // `<T as Trait>::f(...)`
+            TokenTree::Ident(name)
+                if index > 0
+                    && is_punct(&tokens[index - 1], '<')
+                    && next(1).map_or(false, |tt| is_ident(tt, "as")) =>
+            {
+                Some(name.clone())
+            }
+
+            // `x.f(...)`
+            TokenTree::Ident(binding)
+                if next(1).map_or(false, |tt| is_punct(tt, '.'))
+                    && next(2).map_or(false, |tt| matches!(tt, TokenTree::Ident(..)))
+                    && next(3).map_or(false, is_call) =>
+            {
+                bindings
+                    .iter()
+                    .find(|(b, _)| b == binding)
+                    .map(|(_, ty)| ty.clone())
+            }
+
+            _ => None,
+        };
+
+        let param = used.and_then(|name| params.iter_mut().find(|p| p.name == name));
+        if let Some(param) = param.filter(|p| !p.c0nst && !p.used) {
+            param.used = true;
+            cx.warnings.push(Warning::new(
+                token.span(),
+                "missing_c0nst_bound",
+                format!(
+                    "`{}` is used in a trait call without a `c0nst` or `[c0nst]` bound, \
+                     which fails in a `c0nst fn` on nightly",
+                    param.name
+                ),
+            ));
+        }
+    }
+}
+
+/// Warns about constructs in the body of a `c0nst fn` which are not allowed
+/// in a const fn, skipping nested items.
+fn constructs(tokens: &[TokenTree], cx: &mut Context) {
+    for (index, token) in tokens.iter().enumerate() {
+        let next = |offset: usize| tokens.get(index + offset);
+        let prev = index.checked_sub(1).map(|i| &tokens[i]);
+
+        let construct = match token {
+            TokenTree::Group(group) => {
+                let start = item::start(tokens, index);
+                let nested = tokens[start..index].iter().any(|tt| {
+                    ["fn", "impl", "trait", "mod"]
+                        .iter()
+                        .any(|kw| is_ident(tt, kw))
+                });
+
+                if !(nested && group.delimiter() == Delimiter::Brace) {
+                    let inner: Vec<_> = group.stream().into_iter().collect();
+                    constructs(&inner, cx);
+                }
+
+                continue;
+            }
+
+            // `for x in iter { ... }`, but not `for<'a>`
+            TokenTree::Ident(ident)
+                if ident == "for" && !next(1).map_or(false, |tt| is_punct(tt, '<')) =>
+            {
+                "a `for` loop".to_string()
+            }
+
+            // `expr?`, but not `?Sized`
+            TokenTree::Punct(p)
+                if p.as_char() == '?'
+                    && prev.map_or(
+                        false,
+                        |tt| !matches!(tt, TokenTree::Punct(p) if p.as_char() != '?'),
+                    ) =>
+            {
+                "the `?` operator".to_string()
+            }
+
+            // `.iter()`
+            TokenTree::Ident(ident)
+                if prev.map_or(false, |tt| is_punct(tt, '.'))
+                    && ITERATORS.iter().any(|name| ident == name)
+                    && next(1).map_or(false, is_call) =>
+            {
+                format!("`.{ident}()`")
+            }
+
+            // `Box::new(...)`
+            TokenTree::Ident(ident)
+                if ident == "Box"
+                    && is_assoc_call(tokens, index)
+                    && next(3).map_or(false, |tt| is_ident(tt, "new")) =>
+            {
+                "`Box::new`".to_string()
+            }
+
+            // `vec![...]`
+            TokenTree::Ident(ident)
+                if MACROS.iter().any(|name| ident == name)
+                    && next(1).map_or(false, |tt| is_punct(tt, '!')) =>
+            {
+                format!("`{ident}!`")
+            }
+
+            _ => continue,
+        };
+
+        cx.warnings.push(Warning::new(
+            token.span(),
+            "non_const_body",
+            format!("{construct} is not const, which fails in a `c0nst fn` on nightly"),
+        ));
+    }
+}
+
+/// Returns whether `T::f(...)` starts at `index`, but not `path::T::f(...)`.
+fn is_assoc_call(tokens: &[TokenTree], index: usize) -> bool {
+    let joint = |tt: &TokenTree| matches!(tt, TokenTree::Punct(p) if p.as_char() == ':' && p.spacing() == Spacing::Joint);
+    let path = index > 0 && is_punct(&tokens[index - 1], ':');
+    let separator = tokens.get(index + 1).map_or(false, joint);
+
+    !path
+        && separator
+        && matches!(tokens.get(index + 3), Some(TokenTree::Ident(..)))
+        && tokens.get(index + 4).map_or(false, is_call)
+}
+
+fn is_body(token: &TokenTree) -> bool {
+    match token {
+        TokenTree::Group(g) => g.delimiter() == Delimiter::Brace,
+        TokenTree::Punct(p) => p.as_char() == ';',
+        _ => false,
+    }
+}
+
+fn is_call(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Group(g) if g.delimiter() == Delimiter::Parenthesis)
+}
@@ -1,6 +1,34 @@
+mod check;
+mod destruct;
+mod doc;
+mod drop;
+mod infer;
+mod inherent;
+mod item;
+mod keep;
+mod lint;
+mod only;
+mod path;
+mod prepare;
+mod redundant;
 mod rule;
+mod select;
 mod stream;
 mod subslice;
+#[cfg(feature = "syn")]
+mod syntax;
+mod token;
+
+use proc_macro2::TokenStream;
+
+pub use self::doc::unquote;
+pub use self::item::items;
+use self::item::Scope;
+pub use self::path::is_path;
+pub use self::rule::{Custom, Keyword, State};
+use crate::error::Error;
+use crate::options::Options;
+use crate::warning::Warning;
 
 /// Target compilation environment
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -15,8 +43,118 @@ pub enum Target {
     Nightly,
 }
 
+impl Target {
+    /// Returns whether `Keyword::C0NST` is enabled on this target.
+    pub fn state(self) -> State {
+        match self {
+            Self::Nightly => State::Enabled,
+            Self::Stable => State::Disabled,
+        }
+    }
+}
+
+/// Nightly const trait syntax, selected by the build script
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Profile {
+    /// `#[const_trait] trait` with `~const` bounds
+    #[cfg_attr(c0nst_profile = "tilde", default)]
+    Tilde,
+
+    /// `#[const_trait] trait` with `[const]` bounds
+    #[cfg_attr(c0nst_profile = "bracket", default)]
+    Bracket,
+
+    /// `const trait` with `[const]` bounds
+    #[cfg_attr(not(any(c0nst_profile = "tilde", c0nst_profile = "bracket")), default)]
+    Current,
+}
+
+impl Profile {
+    /// Whether const traits are marked with `#[const_trait]`
+    pub fn trait_attribute(self) -> bool {
+        self != Self::Current
+    }
+
+    /// Whether maybe-const bounds are written `~const`
+    pub fn tilde(self) -> bool {
+        self == Self::Tilde
+    }
+}
+
+/// Engine which rewrites the `c0nst` markers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Token pattern matching
+    #[cfg_attr(not(feature = "syn"), default)]
+    Tokens,
+
+    /// Parsing with `syn`
+    #[cfg(feature = "syn")]
+    #[default]
+    Syntax,
+}
+
+impl Engine {
+    /// All available engines
+    #[allow(dead_code)]
+    pub const ALL: &'static [Self] = &[
+        Self::Tokens,
+        #[cfg(feature = "syn")]
+        Self::Syntax,
+    ];
+}
+
+/// Settings and diagnostics for a single macro invocation
+#[derive(Clone, Debug, Default)]
+pub struct Context {
+    pub target: Target,
+    pub profile: Profile,
+    pub engine: Engine,
+    pub options: Options,
+    pub errors: Vec<Error>,
+    pub warnings: Vec<Warning>,
+
+    /// The item whose body is being converted
+    pub scope: Scope,
+
+    /// The aliases of `Destruct` declared by `use` in the enclosing blocks
+    pub destruct: Vec<proc_macro2::Ident>,
+}
+
+/// Replaces the placeholder of `keyword`, without any of the handling
+/// specific to `c0nst`.
+pub fn apply_keyword(stream: TokenStream, keyword: &Keyword, state: State) -> TokenStream {
+    stream::keyword(stream, keyword, state)
+}
+
 pub trait Convert {
     type Output;
 
-    fn convert(self, target: Target) -> Self::Output;
+    fn convert(self, cx: &mut Context) -> Self::Output;
+}
+
+impl Convert for TokenStream {
+    type Output = TokenStream;
+
+    fn convert(self, cx: &mut Context) -> Self::Output {
+        let prepared = prepare::prepare(self, cx);
+        if !cx.errors.is_empty() {
+            return prepared;
+        }
+
+        match cx.engine {
+            Engine::Tokens => stream::rewrite(prepared, cx),
+
+            #[cfg(feature = "syn")]
+            Engine::Syntax => {
+                let prepared = stream::custom(prepared, cx);
+                let output = syntax::rewrite(prepared, cx);
+                match cx.target == Target::Nightly && cx.profile.tilde() {
+                    true if !cx.options.rules.is_empty() => stream::respell(output),
+                    _ => output,
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,100 @@
+//! `#[c0nst(nightly_only)]` and `#[c0nst(stable_only)]`, which keep an item
+//! on one target and remove it entirely on the other
+//!
+//! Besides items and statements, the attributes also apply to the elements of
+//! a comma-separated list: fields, variants and the contents of parentheses
+//! and brackets, such as function parameters.
+
+use std::ops::Range;
+
+use proc_macro2::{Delimiter, Group, TokenTree};
+
+use super::item::{self, Scope};
+use super::token::{is_ident, is_punct, KEYWORD};
+use super::{Context, Target};
+use crate::error::Error;
+
+/// Removes the items at this level which are marked for the other target.
+pub fn filter(tokens: &mut Vec<TokenTree>, cx: &mut Context) {
+    // Blocks and item bodies hold statements and items, never a list.
+    let list = matches!(cx.scope, Scope::Fields | Scope::Other);
+    let mut index = 0;
+
+    while index + 1 < tokens.len() {
+        let only = match &tokens[index + 1] {
+            TokenTree::Group(group) if is_punct(&tokens[index], '#') => target(group),
+            _ => None,
+        };
+
+        match only {
+            None => index += 1,
+
+            Some(Ok(target)) if target == cx.target => {
+                tokens.drain(index..index + 2);
+            }
+
+            Some(Ok(..)) => {
+                let range = match list {
+                    true => element(tokens, index),
+                    false => {
+                        let start = item::start(tokens, index);
+                        start..item::end(tokens, start)
+                    }
+                };
+
+                index = range.start;
+                tokens.drain(range);
+            }
+
+            Some(Err(error)) => {
+                cx.errors.push(error);
+                tokens.drain(index..index + 2);
+            }
+        }
+    }
+}
+
+/// Returns the element of the comma-separated list containing the token at
+/// `index`, including its trailing comma.
+fn element(tokens: &[TokenTree], index: usize) -> Range<usize> {
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (position, tt) in tokens.iter().enumerate() {
+        if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') && !(position > 0 && is_punct(&tokens[position - 1], '-')) {
+            depth = depth.saturating_sub(1);
+        } else if is_punct(tt, ',') && depth == 0 {
+            if position > index {
+                return start..position + 1;
+            }
+            start = position + 1;
+        }
+    }
+
+    start..tokens.len()
+}
+
+/// Returns the target of the attribute body `[c0nst(...)]`.
+fn target(group: &Group) -> Option<Result<Target, Error>> {
+    if group.delimiter() != Delimiter::Bracket {
+        return None;
+    }
+
+    let inner: Vec<_> = group.stream().into_iter().collect();
+    let args = match inner.as_slice() {
+        [name, TokenTree::Group(args)] if is_ident(name, KEYWORD) => args,
+        _ => return None,
+    };
+
+    let args: Vec<_> = args.stream().into_iter().collect();
+    Some(match args.as_slice() {
+        [arg] if is_ident(arg, "nightly_only") => Ok(Target::Nightly),
+        [arg] if is_ident(arg, "stable_only") => Ok(Target::Stable),
+        _ => Err(Error::new(
+            group.span(),
+            "expected `#[c0nst(nightly_only)]` or `#[c0nst(stable_only)]`",
+        )),
+    })
+}
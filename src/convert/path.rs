@@ -0,0 +1,61 @@
+//! Paths to items of `core` which are injected into the output
+//!
+//! Injected paths start at `::core`, or at the root set with
+//! `#![c0nst(root = "...")]`, so a local module named `core` cannot shadow
+//! them. Their tokens have mixed-site hygiene, located at the token they
+//! replace.
+
+use proc_macro2::{Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Creates the path `::core::marker::Destruct` located at `span`, starting
+/// at `root` instead of `::core` if given.
+pub fn destruct(root: Option<&TokenStream>, span: Span) -> Vec<TokenTree> {
+    let span = Span::mixed_site().located_at(span);
+
+    let mut path: Vec<_> = match root {
+        Some(root) => root.clone().into_iter().collect(),
+        None => separator().chain(ident("core")).collect(),
+    };
+
+    path.extend(separator().chain(ident("marker")));
+    path.extend(separator().chain(ident("Destruct")));
+
+    for token in &mut path {
+        token.set_span(span);
+    }
+
+    path
+}
+
+/// Returns whether the tokens are a path such as `::facade::core`.
+pub fn is_path(tokens: &TokenStream) -> bool {
+    let tokens: Vec<_> = tokens.clone().into_iter().collect();
+    let segments = match tokens.as_slice() {
+        [TokenTree::Punct(a), TokenTree::Punct(b), rest @ ..]
+            if a.as_char() == ':' && b.as_char() == ':' =>
+        {
+            rest
+        }
+        all => all,
+    };
+
+    !segments.is_empty()
+        && segments.chunks(3).all(|segment| match segment {
+            [TokenTree::Ident(..)] => true,
+            [TokenTree::Ident(..), TokenTree::Punct(a), TokenTree::Punct(b)] => {
+                a.as_char() == ':' && b.as_char() == ':'
+            }
+            _ => false,
+        })
+        && segments.len() % 3 == 1
+}
+
+fn separator() -> impl Iterator<Item = TokenTree> {
+    [Spacing::Joint, Spacing::Alone]
+        .into_iter()
+        .map(|spacing| Punct::new(':', spacing).into())
+}
+
+fn ident(name: &str) -> std::iter::Once<TokenTree> {
+    std::iter::once(Ident::new(name, Span::call_site()).into())
+}
@@ -0,0 +1,130 @@
+use proc_macro2::{Delimiter, Group, TokenStream, TokenTree};
+
+use super::item::{self, Scope};
+use super::token::is_fn_marker;
+use super::{
+    check, destruct, doc, drop, infer, inherent, keep, lint, only, redundant, select, Context,
+    Target,
+};
+
+/// Validates the input and resolves everything which depends on the
+/// surrounding items, leaving only `c0nst` markers for the rewriting engine.
+pub fn prepare(stream: TokenStream, cx: &mut Context) -> TokenStream {
+    let mut tokens: Vec<_> = stream.into_iter().collect();
+    select::select(&mut tokens, cx);
+    check::check(&tokens, cx);
+    only::filter(&mut tokens, cx);
+
+    let aliases = cx.destruct.len();
+    destruct::aliases(&mut tokens, cx);
+    destruct::normalize(&mut tokens, cx);
+    item::order(&mut tokens);
+
+    if cx.scope.has_items() {
+        drop::derive(&mut tokens, cx);
+    }
+
+    // Record the scope of each brace group before rewriting its header. Items
+    // may also be declared inside a function body, so every level is checked.
+    let mut scopes = Vec::new();
+    for index in 0..tokens.len() {
+        match &tokens[index] {
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => {
+                scopes.push(Scope::of(&tokens, index));
+            }
+            _ => {}
+        }
+    }
+
+    inherent::unmark(&mut tokens, &scopes);
+
+    if let Scope::Impl {
+        c0nst: true,
+        inherent: true,
+        ..
+    } = cx.scope
+    {
+        inherent::propagate(&mut tokens, cx);
+    }
+
+    // Methods of a `c0nst trait` or an `impl c0nst Trait` are const
+    // through the trait, so their own `c0nst` is dropped.
+    let owner = match cx.scope {
+        Scope::Trait { c0nst: true } => Some("a `c0nst trait`"),
+        Scope::Impl {
+            c0nst: true,
+            inherent: false,
+            ..
+        } => Some("an `impl c0nst Trait`"),
+        _ => None,
+    };
+
+    if let Some(owner) = owner {
+        let mut index = 0;
+        while index < tokens.len() {
+            if is_fn_marker(&tokens, index) {
+                redundant::method(tokens[index].span(), owner, cx);
+                tokens.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    if cx.options.infer_bounds && cx.scope.has_items() {
+        infer::infer(&mut tokens);
+    }
+
+    infer::opt_out(&mut tokens, cx);
+    redundant::bounds(&tokens, cx);
+
+    if cx.options.lint {
+        lint::lint(&tokens, cx);
+    }
+
+    if cx.target == Target::Stable && cx.options.stable_const {
+        keep::keep(&mut tokens, cx.scope);
+    }
+
+    let mut output = TokenStream::new();
+    let mut attribute = false;
+    let mut scopes = scopes.into_iter();
+    let scope = cx.scope;
+
+    for token in tokens {
+        let token = match token {
+            TokenTree::Group(group) => {
+                let docs = match attribute && cx.options.docs {
+                    true => doc::rewrite(&group, cx.target, cx.profile),
+                    false => None,
+                };
+
+                TokenTree::Group(docs.unwrap_or_else(|| {
+                    cx.scope = match group.delimiter() {
+                        Delimiter::Brace => scopes.next().unwrap_or(Scope::Other),
+                        _ => Scope::Other,
+                    };
+
+                    let stream = prepare(group.stream(), cx);
+                    cx.scope = scope;
+
+                    let mut prepared = Group::new(group.delimiter(), stream);
+                    prepared.set_span(group.span());
+                    prepared
+                }))
+            }
+            tt => tt,
+        };
+
+        // Track whether the next group is the body of `#[...]` or `#![...]`.
+        attribute = match &token {
+            TokenTree::Punct(p) => p.as_char() == '#' || (attribute && p.as_char() == '!'),
+            _ => false,
+        };
+
+        output.extend(std::iter::once(token));
+    }
+
+    cx.destruct.truncate(aliases);
+    output
+}
@@ -0,0 +1,84 @@
+//! Warnings about `c0nst` markers which have no effect
+//!
+//! The input is accepted, but the marker is most likely a mistake.
+
+use proc_macro2::{Delimiter, Span, TokenTree};
+
+use super::token::{is_ident, is_marker, is_maybe, is_punct, split};
+use super::Context;
+use crate::warning::Warning;
+
+/// Primitive types, which never have a const or non-const choice.
+pub const PRIMITIVES: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64",
+];
+
+/// Warns about a `c0nst` on a method which is already const through `owner`.
+pub fn method(span: Span, owner: &str, cx: &mut Context) {
+    cx.warnings.push(Warning::new(
+        span,
+        "redundant_c0nst",
+        format!("`c0nst` has no effect: the methods of {owner} are already const"),
+    ));
+}
+
+/// Warns about `c0nst` and `[c0nst]` bounds on primitive types in the where
+/// clauses at this level, such as `where u8: [c0nst] Default`.
+pub fn bounds(tokens: &[TokenTree], cx: &mut Context) {
+    for (index, tt) in tokens.iter().enumerate() {
+        if !is_ident(tt, "where") {
+            continue;
+        }
+
+        let clause = &tokens[index + 1..];
+        let mut depth = 0usize;
+        let end = clause
+            .iter()
+            .enumerate()
+            .position(|(i, tt)| {
+                if is_punct(tt, '<') {
+                    depth += 1;
+                } else if is_punct(tt, '>') && !(i > 0 && is_punct(&clause[i - 1], '-')) {
+                    depth = depth.saturating_sub(1);
+                }
+
+                depth == 0
+                    && match tt {
+                        TokenTree::Group(g) => g.delimiter() == Delimiter::Brace,
+                        TokenTree::Punct(p) => p.as_char() == ';' || p.as_char() == '=',
+                        _ => false,
+                    }
+            })
+            .unwrap_or(clause.len());
+
+        for predicate in split(&clause[..end]) {
+            let (ty, bounds) = match predicate {
+                [TokenTree::Ident(ty), colon, bounds @ ..]
+                    if is_punct(colon, ':')
+                        && !bounds.first().map_or(false, |tt| is_punct(tt, ':')) =>
+                {
+                    (ty, bounds)
+                }
+                _ => continue,
+            };
+
+            if !PRIMITIVES.iter().any(|p| ty == p) {
+                continue;
+            }
+
+            for marker in bounds.iter().filter(|tt| is_marker(tt)) {
+                let written = match is_maybe(marker) {
+                    true => "[c0nst]",
+                    false => "c0nst",
+                };
+
+                cx.warnings.push(Warning::new(
+                    marker.span(),
+                    "redundant_c0nst_bound",
+                    format!("`{written}` has no effect on a bound of the concrete type `{ty}`"),
+                ));
+            }
+        }
+    }
+}
@@ -1,17 +1,24 @@
-use proc_macro2::{TokenStream, TokenTree};
+use proc_macro2::{Span, TokenStream, TokenTree};
 
-pub struct Rule<'a>(&'a str, &'a str);
+use super::doc::unquote;
+use super::subslice::Subslice;
+use super::token::{is_marker, is_punct, KEYWORD};
+use crate::error::Error;
+
+#[derive(Clone)]
+pub struct Rule<'a>(&'a str, &'a str, &'a str);
 
 impl Rule<'_> {
+    /// Rules for specific bounds, applied before any declared rules
+    ///
+    /// `Destruct` in a replacement stands for the full path to the trait.
     pub const RULES: &'static [Rule<'static>] = &[
-        Rule("[c0nst] Destruct +", "[const] core::marker::Destruct +"),
-        Rule("+ [c0nst] Destruct", "+ [const] core::marker::Destruct"),
-        Rule(": [c0nst] Destruct", ": [const] core::marker::Destruct"),
-        Rule("c0nst Destruct +", "const core::marker::Destruct +"),
-        Rule("+ c0nst Destruct", "+ const core::marker::Destruct"),
-        Rule(": c0nst Destruct", ": const core::marker::Destruct"),
-        Rule("[c0nst]", "[const]"),
-        Rule("c0nst", "const"),
+        Rule("[c0nst] Destruct +", "[const] Destruct +", ""),
+        Rule("+ [c0nst] Destruct", "+ [const] Destruct", ""),
+        Rule(": [c0nst] Destruct", ": [const] Destruct", ""),
+        Rule("c0nst Destruct +", "const Destruct +", ""),
+        Rule("+ c0nst Destruct", "+ const Destruct", ""),
+        Rule(": c0nst Destruct", ": const Destruct", ""),
     ];
 
     pub fn pattern(&self) -> Vec<TokenTree> {
@@ -23,4 +30,153 @@ impl Rule<'_> {
         let stream: TokenStream = self.1.parse().expect("valid nightly");
         stream.into_iter().collect()
     }
+
+    pub fn stable(&self) -> Vec<TokenTree> {
+        let stream: TokenStream = self.2.parse().expect("valid stable");
+        stream.into_iter().collect()
+    }
+
+    /// Returns the replacement when the keyword is in `state`.
+    pub fn replacement(&self, state: State) -> Vec<TokenTree> {
+        match state {
+            State::Enabled => self.nightly(),
+            State::Disabled => self.stable(),
+        }
+    }
+}
+
+/// Whether the keyword of a `Keyword` is emitted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    /// The placeholder becomes the keyword
+    Enabled,
+
+    /// The placeholder is removed
+    Disabled,
+}
+
+/// A placeholder keyword which stands for a real keyword
+///
+/// `placeholder` and its bracketed bound form `[placeholder]` become `keyword`
+/// and `[keyword]` when it is enabled, and are removed otherwise. `c0nst!`
+/// enables `Keyword::C0NST` on nightly, and `cond_kw!` enables its keyword
+/// under a `cfg` predicate.
+#[derive(Clone, Copy, Debug)]
+pub struct Keyword<'a> {
+    pub placeholder: &'a str,
+    pub keyword: &'a str,
+}
+
+impl Keyword<'_> {
+    /// The keyword of `c0nst!`
+    pub const C0NST: Keyword<'static> = Keyword {
+        placeholder: KEYWORD,
+        keyword: "const",
+    };
+
+    /// Returns the rules for the bracketed and the plain placeholder, whose
+    /// nightly and stable replacements apply when it is enabled and disabled.
+    pub fn rules(&self) -> [Custom; 2] {
+        let rule = |pattern: String, keyword: String| Custom {
+            pattern,
+            nightly: keyword,
+            stable: String::new(),
+        };
+
+        [
+            rule(
+                format!("[{}]", self.placeholder),
+                format!("[{}]", self.keyword),
+            ),
+            rule(self.placeholder.to_string(), self.keyword.to_string()),
+        ]
+    }
+}
+
+/// A rule declared with `#![c0nst(rule = "pattern" => "nightly" / "stable")]`
+/// or generated for a `Keyword`
+#[derive(Clone, Debug)]
+pub struct Custom {
+    pattern: String,
+    nightly: String,
+    stable: String,
+}
+
+impl Custom {
+    /// Parses the arguments after `rule =`.
+    ///
+    /// The stable replacement may be left out, which removes the pattern.
+    pub fn parse(span: Span, args: &[TokenTree]) -> Result<Self, Error> {
+        let (pattern, nightly, stable) = match args {
+            [pattern, eq, gt, nightly] if is_arrow(eq, gt) => (pattern, nightly, None),
+            [pattern, eq, gt, nightly, slash, stable]
+                if is_arrow(eq, gt) && is_punct(slash, '/') =>
+            {
+                (pattern, nightly, Some(stable))
+            }
+            _ => {
+                return Err(Error::new(
+                    span,
+                    "expected `rule = \"pattern\" => \"nightly\" / \"stable\"`",
+                ))
+            }
+        };
+
+        let rule = Self {
+            pattern: tokens(pattern)?,
+            nightly: tokens(nightly)?,
+            stable: match stable {
+                Some(stable) => tokens(stable)?,
+                None => String::new(),
+            },
+        };
+
+        let parsed = rule.rule();
+        let pattern = parsed.pattern();
+        if !pattern.iter().any(is_marker) {
+            return Err(Error::new(
+                args[0].span(),
+                "the pattern of a rule must contain `c0nst` or `[c0nst]`",
+            ));
+        }
+
+        // Rules are applied until their pattern no longer matches.
+        let mut replacements = vec![(nightly, parsed.nightly())];
+        replacements.extend(stable.map(|stable| (stable, parsed.stable())));
+        for (literal, replacement) in replacements {
+            if replacement.find_subslice(&pattern).is_some() {
+                return Err(Error::new(
+                    literal.span(),
+                    "the replacement of a rule must not contain its pattern",
+                ));
+            }
+        }
+
+        Ok(rule)
+    }
+
+    pub fn rule(&self) -> Rule<'_> {
+        Rule(&self.pattern, &self.nightly, &self.stable)
+    }
+}
+
+fn is_arrow(eq: &TokenTree, gt: &TokenTree) -> bool {
+    is_punct(eq, '=') && is_punct(gt, '>')
+}
+
+/// Returns the text of a string literal which holds valid tokens.
+fn tokens(literal: &TokenTree) -> Result<String, Error> {
+    let text = match literal {
+        TokenTree::Literal(literal) => unquote(&literal.to_string()),
+        _ => None,
+    };
+
+    let text = text.ok_or_else(|| Error::new(literal.span(), "expected a string literal"))?;
+    match text.parse::<TokenStream>() {
+        Ok(..) => Ok(text),
+        Err(error) => Err(Error::new(
+            literal.span(),
+            format!("invalid tokens in rule: {error}"),
+        )),
+    }
 }
@@ -0,0 +1,180 @@
+//! `c0nst::select!`, which keeps the branch for the current target
+//!
+//! ```text
+//! c0nst::select! {
+//!     nightly => { while i < n { i += 1; } },
+//!     stable => { i = (i..n).last().map_or(i, |last| last + 1); },
+//! }
+//! ```
+//!
+//! At item level the items of the branch replace the invocation, and so do
+//! the statements of the branch where a statement starts, which keeps its
+//! `let` bindings visible after the invocation, unless the branch ends with
+//! an expression which neither `;` nor the end of the block follows. Anywhere
+//! else the branch is kept as a block, which makes it an expression.
+
+use proc_macro2::{Delimiter, Group, Span, TokenTree};
+
+use super::item::Scope;
+
+use super::token::{is_ident, is_punct, KEYWORD};
+use super::{Context, Target};
+use crate::error::Error;
+
+/// Replaces each `c0nst::select!` at this level with its selected branch.
+pub fn select(tokens: &mut Vec<TokenTree>, cx: &mut Context) {
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let (start, body) = match invocation(tokens, index) {
+            Some(found) => found,
+            None => {
+                index += 1;
+                continue;
+            }
+        };
+
+        let branch = match branch(&body, cx.target) {
+            Ok(branch) => branch,
+            Err(error) => {
+                cx.errors.push(error);
+                tokens.drain(start..=index);
+                index = start;
+                continue;
+            }
+        };
+
+        let mut end = index + 1;
+        let contents: Vec<_> = branch
+            .as_ref()
+            .map(|g| g.stream().into_iter().collect())
+            .unwrap_or_default();
+
+        // A trailing expression of the branch can only be spliced where it
+        // is followed by `;` or ends the block.
+        let complete = contents.last().map_or(true, |tt| is_punct(tt, ';'));
+        let statement = cx.scope == Scope::Block
+            && is_statement_start(tokens, start)
+            && (complete || tokens.get(end).map_or(true, |tt| is_punct(tt, ';')));
+
+        let replacement = match cx.scope.has_items() || statement {
+            true => {
+                // `select! { ... };` is allowed like any other macro, but a
+                // trailing expression of the branch keeps its `;`.
+                if (cx.scope.has_items() || complete)
+                    && tokens.get(end).map_or(false, |tt| is_punct(tt, ';'))
+                {
+                    end += 1;
+                }
+
+                contents
+            }
+            false => {
+                let block = branch.unwrap_or_else(|| {
+                    let mut empty = Group::new(Delimiter::Brace, Default::default());
+                    empty.set_span(body.span());
+                    empty
+                });
+
+                vec![TokenTree::Group(block)]
+            }
+        };
+
+        // The branch may contain another `select!`, which is found next.
+        tokens.splice(start..end, replacement);
+        index = start;
+    }
+}
+
+/// Returns the start of `c0nst::select!` or `::c0nst::select!` if its body is
+/// at `index`, together with the body.
+fn invocation(tokens: &[TokenTree], index: usize) -> Option<(usize, Group)> {
+    let body = match &tokens[index] {
+        TokenTree::Group(group) if index >= 5 => group.clone(),
+        _ => return None,
+    };
+
+    let path = &tokens[index - 5..index];
+    let matches = is_ident(&path[0], KEYWORD)
+        && is_path_sep(&path[1], &path[2])
+        && is_ident(&path[3], "select")
+        && is_punct(&path[4], '!');
+
+    if !matches {
+        return None;
+    }
+
+    let start = index - 5;
+    match start >= 2 && is_path_sep(&tokens[start - 2], &tokens[start - 1]) {
+        true => Some((start - 2, body)),
+        false => Some((start, body)),
+    }
+}
+
+/// Returns whether a statement starts at `index` of a block.
+fn is_statement_start(tokens: &[TokenTree], index: usize) -> bool {
+    match index.checked_sub(1).map(|i| &tokens[i]) {
+        None => true,
+        Some(TokenTree::Group(group)) => group.delimiter() == Delimiter::Brace,
+        Some(tt) => is_punct(tt, ';'),
+    }
+}
+
+fn is_path_sep(first: &TokenTree, second: &TokenTree) -> bool {
+    is_punct(first, ':') && is_punct(second, ':')
+}
+
+/// Parses `nightly => { ... }, stable => { ... }` and returns the branch for
+/// `target`, if it was given.
+fn branch(body: &Group, target: Target) -> Result<Option<Group>, Error> {
+    let tokens: Vec<_> = body.stream().into_iter().collect();
+    let mut nightly = None;
+    let mut stable = None;
+
+    let mut rest = tokens.as_slice();
+    while !rest.is_empty() {
+        let (name, block, tail) = match rest {
+            [TokenTree::Ident(name), eq, gt, TokenTree::Group(block), tail @ ..]
+                if is_punct(eq, '=')
+                    && is_punct(gt, '>')
+                    && block.delimiter() == Delimiter::Brace =>
+            {
+                (name, block, tail)
+            }
+            [first, ..] => return Err(expected(first.span())),
+            [] => unreachable!(),
+        };
+
+        let slot = match name.to_string().as_str() {
+            "nightly" => &mut nightly,
+            "stable" => &mut stable,
+            _ => return Err(expected(name.span())),
+        };
+
+        if slot.is_some() {
+            return Err(Error::new(
+                name.span(),
+                format!("duplicate `{name}` branch in `c0nst::select!`"),
+            ));
+        }
+
+        *slot = Some(block.clone());
+        rest = match tail {
+            [comma, tail @ ..] if is_punct(comma, ',') => tail,
+            [] => tail,
+            [first, ..] => return Err(expected(first.span())),
+        };
+    }
+
+    Ok(match target {
+        Target::Nightly => nightly,
+        Target::Stable => stable,
+    })
+}
+
+fn expected(span: Span) -> Error {
+    Error::new(
+        span,
+        "expected `nightly => { ... }` or `stable => { ... }` in `c0nst::select!`",
+    )
+}
@@ -1,46 +1,293 @@
-use proc_macro2::{Group, TokenStream, TokenTree};
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
 
-use super::rule::Rule;
+use super::rule::{Keyword, Rule, State};
 use super::subslice::Subslice;
-use super::Target;
+use super::token::{attribute, is_ident, is_maybe_const, is_punct, is_trait_marker};
+use super::{item, path};
+use super::{Context, Target};
 
-impl super::Convert for TokenStream {
-    type Output = TokenStream;
+/// Rewrites `c0nst` markers by matching the token patterns in `Rule::RULES`,
+/// the rules declared with `#![c0nst(rule = ...)]` and the rules of
+/// `Keyword::C0NST`.
+pub fn rewrite(stream: TokenStream, cx: &Context) -> TokenStream {
+    let rewritten = apply(stream, cx, true);
+    let output = keyword(rewritten, &Keyword::C0NST, cx.target.state());
 
-    fn convert(self, target: Target) -> Self::Output {
-        let mut tokens: Vec<_> = self.into_iter().collect();
+    match cx.target == Target::Nightly && cx.profile.tilde() {
+        true => respell(output),
+        false => output,
+    }
+}
+
+/// Replaces the placeholder of `keyword` and its bracketed form.
+pub fn keyword(stream: TokenStream, keyword: &Keyword, state: State) -> TokenStream {
+    let rules = keyword.rules();
+    let mut tokens: Vec<_> = stream.into_iter().collect();
 
-        for rule in Rule::RULES {
-            let pattern = rule.pattern();
-            while let Some(index) = tokens.find_subslice(&pattern) {
-                // Remove the matched pattern.
-                for _ in 0..pattern.len() {
-                    tokens.remove(index);
-                }
+    for rule in rules.iter().map(|rule| rule.rule()) {
+        let pattern = rule.pattern();
 
-                // Insert the replacement tokens.
-                if target == Target::Nightly {
-                    for token in rule.nightly().drain(..).rev() {
-                        tokens.insert(index, token);
-                    }
-                }
+        while let Some(index) = tokens.find_subslice(&pattern) {
+            let matched: Vec<_> = tokens.drain(index..index + pattern.len()).collect();
+            let mut replacement = rule.replacement(state);
+            if !replacement.is_empty() {
+                respan(&mut replacement, &matched);
             }
-        }
 
-        let mut output = Self::new();
+            tokens.splice(index..index, replacement);
+        }
+    }
 
-        for token in tokens {
-            let token = match token {
-                TokenTree::Group(group) => TokenTree::Group(Group::new(
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let mut rewritten = Group::new(
                     group.delimiter(),
-                    group.stream().convert(target),
-                )),
-                tt => tt,
-            };
+                    self::keyword(group.stream(), keyword, state),
+                );
+                rewritten.set_span(group.span());
+                TokenTree::Group(rewritten)
+            }
+            tt => tt,
+        })
+        .collect()
+}
+
+/// Applies only the rules in `cx.options.rules`.
+#[cfg(feature = "syn")]
+pub fn custom(stream: TokenStream, cx: &Context) -> TokenStream {
+    match cx.options.rules.is_empty() {
+        true => stream,
+        false => apply(stream, cx, false),
+    }
+}
+
+fn apply(stream: TokenStream, cx: &Context, builtin: bool) -> TokenStream {
+    let mut tokens: Vec<_> = stream.into_iter().collect();
+    let target = cx.target;
+
+    // Older nightlies mark const traits with an attribute instead.
+    if builtin && target == Target::Nightly && cx.profile.trait_attribute() {
+        while let Some(index) = (0..tokens.len()).find(|&i| is_trait_marker(&tokens, i)) {
+            let marker = tokens.remove(index);
+            let start = item::start(&tokens, index);
+            tokens.splice(start..start, attribute("const_trait", marker.span()));
+        }
+    }
+
+    let rules = match builtin {
+        true => Rule::RULES,
+        false => &[][..],
+    };
+
+    // Declared rules come after the built-in rules for specific bounds and
+    // before the ones of `Keyword::C0NST` for the remaining markers.
+    let custom = cx.options.rules.iter().map(|rule| (rule.rule(), true));
+    let rules = rules.iter().map(|rule| (rule.clone(), false)).chain(custom);
+    let destruct = Rule::RULES.len();
+
+    for (number, (rule, declared)) in rules.enumerate() {
+        let pattern = rule.pattern();
+        let mut from = 0;
+
+        while let Some(offset) = tokens[from..].find_subslice(&pattern) {
+            let index = from + offset;
+            if declared && is_builtin(&tokens, index, pattern.len()) {
+                from = index + 1;
+                continue;
+            }
+
+            // Remove the matched pattern.
+            let matched: Vec<_> = tokens.drain(index..index + pattern.len()).collect();
+
+            // Insert the replacement tokens.
+            let mut replacement = rule.replacement(target.state());
+
+            if !replacement.is_empty() {
+                respan(&mut replacement, &matched);
+            }
+
+            if number < destruct {
+                replacement = replacement
+                    .into_iter()
+                    .flat_map(|tt| match tt {
+                        TokenTree::Ident(ref i) if i == "Destruct" => {
+                            path::destruct(cx.options.root.as_ref(), i.span())
+                        }
+                        tt => vec![tt],
+                    })
+                    .collect();
+            }
+
+            tokens.splice(index..index, replacement);
+            from = 0;
+        }
+    }
+
+    // Removing `: [c0nst] Destruct` on stable may leave `where T {}`.
+    if builtin && target == Target::Stable {
+        tidy_where(&mut tokens);
+    }
+
+    let mut output = TokenStream::new();
+
+    for token in tokens {
+        let token = match token {
+            TokenTree::Group(group) => {
+                let mut rewritten =
+                    Group::new(group.delimiter(), apply(group.stream(), cx, builtin));
+                rewritten.set_span(group.span());
+                TokenTree::Group(rewritten)
+            }
+            tt => tt,
+        };
+
+        output.extend(std::iter::once(token));
+    }
+
+    output
+}
+
+/// Removes the where predicates which have no bounds left, and the `where`
+/// itself if none remain.
+fn tidy_where(tokens: &mut Vec<TokenTree>) {
+    let mut from = 0;
+
+    while let Some(offset) = tokens[from..].iter().position(|tt| is_ident(tt, "where")) {
+        let clause = from + offset;
+        let end = (clause + 1..tokens.len())
+            .find(|&i| match &tokens[i] {
+                TokenTree::Group(g) => g.delimiter() == Delimiter::Brace,
+                tt => is_punct(tt, ';'),
+            })
+            .unwrap_or(tokens.len());
 
-            output.extend(std::iter::once(token));
+        let mut kept = Vec::new();
+        let mut predicate = Vec::new();
+        let mut depth = 0usize;
+
+        for (index, tt) in tokens[clause + 1..end].iter().enumerate() {
+            let previous = index.checked_sub(1).map(|i| &tokens[clause + 1 + i]);
+            if is_punct(tt, '<') {
+                depth += 1;
+            } else if is_punct(tt, '>') && !previous.map_or(false, |tt| is_punct(tt, '-')) {
+                depth = depth.saturating_sub(1);
+            }
+
+            predicate.push(tt.clone());
+            if is_punct(tt, ',') && depth == 0 {
+                keep(&mut kept, &mut predicate);
+            }
         }
+        keep(&mut kept, &mut predicate);
 
-        output
+        let replacement = match kept.is_empty() {
+            true => Vec::new(),
+            false => std::iter::once(tokens[clause].clone())
+                .chain(kept)
+                .collect(),
+        };
+
+        from = clause + replacement.len();
+        tokens.splice(clause..end, replacement);
+    }
+}
+
+/// Moves `predicate` to `kept` if it still has a bound.
+fn keep(kept: &mut Vec<TokenTree>, predicate: &mut Vec<TokenTree>) {
+    let bounded = (0..predicate.len()).any(|i| {
+        is_punct(&predicate[i], ':')
+            && !(i > 0 && is_punct(&predicate[i - 1], ':'))
+            && !predicate.get(i + 1).map_or(false, |tt| is_punct(tt, ':'))
+    });
+
+    match bounded {
+        true => kept.append(predicate),
+        false => predicate.clear(),
     }
 }
+
+/// Returns whether a built-in rule matches tokens of `index..index + len`.
+fn is_builtin(tokens: &[TokenTree], index: usize, len: usize) -> bool {
+    Rule::RULES.iter().any(|rule| {
+        let pattern = rule.pattern();
+        let start = (index + 1).saturating_sub(pattern.len());
+        let end = tokens.len().min(index + len + pattern.len() - 1);
+        tokens[start..end].find_subslice(&pattern).is_some()
+    })
+}
+
+/// Spells each `[const]` bound as `~const`.
+///
+/// `syn` cannot parse `~const`, so this runs after the rewrite.
+pub fn respell(stream: TokenStream) -> TokenStream {
+    stream
+        .into_iter()
+        .flat_map(|token| match token {
+            TokenTree::Group(group) if !is_maybe_const(&group) => {
+                let mut respelled = Group::new(group.delimiter(), respell(group.stream()));
+                respelled.set_span(group.span());
+                vec![TokenTree::Group(respelled)]
+            }
+            tt => tilde(tt),
+        })
+        .collect()
+}
+
+/// Points the replacement tokens back into the input.
+///
+/// Tokens shared with the start or end of the match are the original tokens.
+/// The rest replace the marker, so they take its span.
+fn respan(replacement: &mut [TokenTree], matched: &[TokenTree]) {
+    let same = |(r, m): &(&TokenTree, &TokenTree)| r.to_string() == m.to_string();
+
+    let prefix = replacement.iter().zip(matched).take_while(same).count();
+    let suffix = replacement[prefix..]
+        .iter()
+        .rev()
+        .zip(matched[prefix..].iter().rev())
+        .take_while(same)
+        .count();
+
+    let span = matched[prefix].span();
+    let (start, end) = (replacement.len() - suffix, matched.len() - suffix);
+
+    for (index, token) in replacement.iter_mut().enumerate() {
+        match index {
+            i if i < prefix => *token = matched[i].clone(),
+            i if i >= start => *token = matched[end + i - start].clone(),
+            _ => set_span(token, span),
+        }
+    }
+}
+
+/// Spells a `[const]` bound as `~const`.
+fn tilde(token: TokenTree) -> Vec<TokenTree> {
+    match &token {
+        TokenTree::Group(group) if is_maybe_const(group) => {
+            let mut punct = Punct::new('~', Spacing::Alone);
+            punct.set_span(group.span());
+            vec![punct.into(), Ident::new("const", group.span()).into()]
+        }
+        _ => vec![token],
+    }
+}
+
+/// Sets the span of a token and everything inside it.
+fn set_span(token: &mut TokenTree, span: Span) {
+    if let TokenTree::Group(group) = token {
+        let stream = group
+            .stream()
+            .into_iter()
+            .map(|mut tt| {
+                set_span(&mut tt, span);
+                tt
+            })
+            .collect();
+
+        *group = Group::new(group.delimiter(), stream);
+    }
+
+    token.set_span(span);
+}
@@ -0,0 +1,454 @@
+//! Parse-based rewriting engine built on `syn`.
+//!
+//! The markers are first lowered into syntax which `syn` already understands:
+//!
+//!   * `c0nst trait`, `c0nst fn` and `impl c0nst Trait` become a `#[c0nst]`
+//!     attribute on the item,
+//!   * `c0nst Trait` and `[c0nst] Trait` bounds become the higher-ranked
+//!     binders `for<'c0nst>` and `for<'c0nst_maybe>`,
+//!   * `c0nst { ... }` becomes the labeled block `'c0nst: { ... }`.
+//!
+//! Any other `c0nst` is an ordinary identifier and is left alone. The parsed
+//! file is then rewritten for the target and printed.
+
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+use quote::{quote_spanned, ToTokens};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Attribute, Block, Expr, ExprConst, File, GenericParam, Item, Meta, Token, TypeParamBound,
+};
+
+use super::item::generics;
+use super::path;
+use super::token::{
+    attribute, is_fn_marker, is_ident, is_maybe, is_punct, is_trait_marker, KEYWORD,
+};
+use super::{Context, Profile, Target};
+use crate::error::Error;
+
+/// Lifetime of a lowered `c0nst` bound
+const ALWAYS: &str = "c0nst";
+
+/// Lifetime of a lowered `[c0nst]` bound
+const MAYBE: &str = "c0nst_maybe";
+
+/// Rewrites `c0nst` markers by parsing the input as a file of items.
+///
+/// Input which is not a list of items, such as a lone `c0nst { ... }` block,
+/// is parsed as a list of statements instead.
+pub fn rewrite(stream: TokenStream, cx: &mut Context) -> TokenStream {
+    let lowered = lower(stream, true);
+    let mut rewriter = Rewriter {
+        target: cx.target,
+        profile: cx.profile,
+        root: cx.options.root.clone(),
+    };
+
+    let error = match syn::parse2::<File>(lowered.clone()) {
+        Ok(mut file) => {
+            rewriter.visit_file_mut(&mut file);
+            return file.into_token_stream();
+        }
+        Err(error) => error,
+    };
+
+    match Block::parse_within.parse2(lowered) {
+        Ok(mut stmts) => {
+            let mut output = TokenStream::new();
+            for stmt in &mut stmts {
+                rewriter.visit_stmt_mut(stmt);
+                stmt.to_tokens(&mut output);
+            }
+
+            output
+        }
+        Err(..) => {
+            let errors = error
+                .into_iter()
+                .map(|e| Error::new(e.span(), e.to_string()));
+            cx.errors.extend(errors);
+            TokenStream::new()
+        }
+    }
+}
+
+/// Lowers the markers of one level into syntax `syn` can parse.
+///
+/// Items may only start at the top level and inside braces.
+fn lower(stream: TokenStream, items: bool) -> TokenStream {
+    let tokens: Vec<_> = stream.into_iter().collect();
+    let mut output: Vec<TokenTree> = Vec::new();
+
+    // Start of the current item in `output` and a token to skip.
+    let mut item = 0;
+    let mut skip = None;
+
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        let next = tokens.get(index + 1);
+
+        if skip == Some(index) {
+            index += 1;
+            continue;
+        }
+
+        if is_block_marker(&tokens, index) {
+            output.extend(lifetime(ALWAYS, token.span()));
+            output.push(Punct::new(':', Spacing::Alone).into());
+        } else if is_fn_marker(&tokens, index) || is_trait_marker(&tokens, index) {
+            output.splice(item..item, attribute(KEYWORD, token.span()));
+        } else if is_ident(token, "impl") && items && at_item_start(&output[item..]) {
+            let (params, rest) = generics(&tokens[index + 1..]);
+            if let Some(marker) = rest.first().filter(|tt| is_ident(tt, KEYWORD)) {
+                output.splice(item..item, attribute(KEYWORD, marker.span()));
+                skip = Some(index + 1 + params.len());
+            }
+
+            output.push(token.clone());
+        } else if is_bound_marker(&tokens, index) {
+            let name = match is_maybe(token) {
+                true => MAYBE,
+                false => ALWAYS,
+            };
+
+            // Merge into an existing binder: `c0nst for<'a> Fn()`.
+            let binder = next.map_or(false, |tt| is_ident(tt, "for"))
+                && tokens.get(index + 2).map_or(false, |tt| is_punct(tt, '<'));
+
+            output.push(Ident::new("for", token.span()).into());
+            output.push(Punct::new('<', Spacing::Alone).into());
+            output.extend(lifetime(name, token.span()));
+            match binder {
+                true => {
+                    output.push(Punct::new(',', Spacing::Alone).into());
+                    index += 2;
+                }
+                false => output.push(Punct::new('>', Spacing::Alone).into()),
+            }
+        } else {
+            let token = match token {
+                TokenTree::Group(group) => {
+                    let brace = group.delimiter() == Delimiter::Brace;
+                    let mut lowered = Group::new(group.delimiter(), lower(group.stream(), brace));
+                    lowered.set_span(group.span());
+                    TokenTree::Group(lowered)
+                }
+                tt => tt.clone(),
+            };
+
+            let end = is_punct(&token, ';') || is_brace(&token);
+            output.push(token);
+            if end {
+                item = output.len();
+            }
+        }
+
+        index += 1;
+    }
+
+    output.into_iter().collect()
+}
+
+fn is_brace(token: &TokenTree) -> bool {
+    matches!(token, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace)
+}
+
+/// Returns whether the token is a `c0nst` marking the block after it.
+fn is_block_marker(tokens: &[TokenTree], index: usize) -> bool {
+    // The return type in `fn f() -> c0nst { ... }`.
+    let arrow =
+        index >= 2 && is_punct(&tokens[index - 1], '>') && is_punct(&tokens[index - 2], '-');
+
+    is_ident(&tokens[index], KEYWORD) && tokens.get(index + 1).map_or(false, is_brace) && !arrow
+}
+
+/// Returns whether the token is a `c0nst` or `[c0nst]` marking a bound.
+fn is_bound_marker(tokens: &[TokenTree], index: usize) -> bool {
+    if !is_ident(&tokens[index], KEYWORD) && !is_maybe(&tokens[index]) {
+        return false;
+    }
+
+    let before = match index.checked_sub(1) {
+        Some(before) => &tokens[before],
+        None => return false,
+    };
+
+    // A single `:`, not the end of a path separator.
+    let colon = is_punct(before, ':')
+        && !matches!(index.checked_sub(2).map(|i| &tokens[i]), Some(TokenTree::Punct(p)) if p.as_char() == ':' && p.spacing() == Spacing::Joint);
+
+    let follows =
+        colon || is_punct(before, '+') || is_ident(before, "impl") || is_ident(before, "dyn");
+    let path = match tokens.get(index + 1) {
+        Some(TokenTree::Ident(..)) => true,
+        Some(tt) => is_punct(tt, ':'),
+        None => false,
+    };
+
+    follows && path
+}
+
+/// Returns whether only attributes and modifiers precede the tokens.
+fn at_item_start(tokens: &[TokenTree]) -> bool {
+    tokens.iter().all(|tt| match tt {
+        TokenTree::Punct(p) => p.as_char() == '#',
+        TokenTree::Group(g) => g.delimiter() == Delimiter::Bracket,
+        TokenTree::Ident(i) => i == "unsafe" || i == "default",
+        TokenTree::Literal(..) => false,
+    })
+}
+
+fn lifetime(name: &str, span: Span) -> [TokenTree; 2] {
+    let mut quote = Punct::new('\'', Spacing::Joint);
+    quote.set_span(span);
+    [quote.into(), Ident::new(name, span).into()]
+}
+
+/// Removes the `#[c0nst]` attribute, returning whether it was present.
+fn take_marker(attrs: &mut Vec<Attribute>) -> Option<Span> {
+    let index = attrs
+        .iter()
+        .position(|attr| matches!(&attr.meta, Meta::Path(path) if path.is_ident(KEYWORD)))?;
+
+    Some(attrs.remove(index).pound_token.span)
+}
+
+/// Inserts `const` into the printed item before the token at `index`.
+fn constify(item: &impl ToTokens, index: impl Fn(&[TokenTree]) -> usize, span: Span) -> Item {
+    let mut tokens: Vec<_> = item.to_token_stream().into_iter().collect();
+    let index = index(&tokens);
+    tokens.insert(index, Ident::new("const", span).into());
+    Item::Verbatim(tokens.into_iter().collect())
+}
+
+struct Rewriter {
+    target: Target,
+    profile: Profile,
+    root: Option<TokenStream>,
+}
+
+impl Rewriter {
+    /// Rewrites a list of bounds, dropping those removed for the target.
+    fn bounds(&self, bounds: &mut Punctuated<TypeParamBound, Token![+]>) {
+        for pair in std::mem::take(bounds).into_pairs() {
+            let (bound, plus) = pair.into_tuple();
+
+            if let Some(bound) = self.bound(bound) {
+                bounds.push_value(bound);
+                if let Some(plus) = plus {
+                    bounds.push_punct(plus);
+                }
+            }
+        }
+
+        if bounds.trailing_punct() {
+            bounds.pop_punct();
+        }
+    }
+
+    fn bound(&self, bound: TypeParamBound) -> Option<TypeParamBound> {
+        let mut bound = match bound {
+            TypeParamBound::Trait(bound) => bound,
+            bound => return Some(bound),
+        };
+
+        let binder = match &mut bound.lifetimes {
+            Some(binder) => binder,
+            None => return Some(TypeParamBound::Trait(bound)),
+        };
+
+        let marker = binder.lifetimes.iter().find_map(|param| match param {
+            GenericParam::Lifetime(lt)
+                if lt.lifetime.ident == ALWAYS || lt.lifetime.ident == MAYBE =>
+            {
+                Some(lt.lifetime.clone())
+            }
+            _ => None,
+        });
+
+        let marker = match marker {
+            Some(marker) => marker,
+            None => return Some(TypeParamBound::Trait(bound)),
+        };
+
+        binder.lifetimes = std::mem::take(&mut binder.lifetimes)
+            .into_iter()
+            .filter(|param| !matches!(param, GenericParam::Lifetime(lt) if lt.lifetime == marker))
+            .collect();
+
+        if binder.lifetimes.is_empty() {
+            bound.lifetimes = None;
+        }
+
+        let destruct = bound.path.is_ident("Destruct");
+        let span = marker.ident.span();
+
+        match self.target {
+            Target::Stable if destruct => None,
+            Target::Stable => Some(TypeParamBound::Trait(bound)),
+            Target::Nightly => {
+                if destruct {
+                    let ident = bound.path.segments[0].ident.span();
+                    let path = path::destruct(self.root.as_ref(), ident)
+                        .into_iter()
+                        .collect();
+                    bound.path = syn::parse2(path).expect("valid path");
+                }
+
+                let maybe = marker.ident == MAYBE;
+                Some(TypeParamBound::Verbatim(match maybe {
+                    true if self.profile.tilde() => quote_spanned!(span=> ~const #bound),
+                    true => quote_spanned!(span=> [const] #bound),
+                    false => quote_spanned!(span=> const #bound),
+                }))
+            }
+        }
+    }
+}
+
+impl VisitMut for Rewriter {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        visit_mut::visit_item_mut(self, item);
+
+        match item {
+            Item::Trait(trait_) => {
+                if let Some(span) = take_marker(&mut trait_.attrs) {
+                    if self.target == Target::Nightly && self.profile.trait_attribute() {
+                        let attr = syn::parse_quote_spanned!(span=> #[const_trait]);
+                        trait_.attrs.insert(0, attr);
+                    } else if self.target == Target::Nightly {
+                        let keyword = |tokens: &[TokenTree]| {
+                            tokens
+                                .iter()
+                                .position(|tt| is_ident(tt, "trait"))
+                                .unwrap_or(0)
+                        };
+
+                        *item = constify(trait_, keyword, span);
+                    }
+                }
+            }
+
+            Item::Impl(impl_) => {
+                if let Some(span) = take_marker(&mut impl_.attrs) {
+                    if self.target == Target::Nightly {
+                        let after = |tokens: &[TokenTree]| {
+                            let index = tokens
+                                .iter()
+                                .position(|tt| is_ident(tt, "impl"))
+                                .unwrap_or(0);
+                            index + 1 + generics(&tokens[index + 1..]).0.len()
+                        };
+
+                        *item = constify(impl_, after, span);
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    fn visit_item_fn_mut(&mut self, item: &mut syn::ItemFn) {
+        visit_mut::visit_item_fn_mut(self, item);
+
+        if let Some(span) = take_marker(&mut item.attrs) {
+            if self.target == Target::Nightly {
+                item.sig.constness = Some(Token![const](span));
+            }
+        }
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item: &mut syn::ImplItemFn) {
+        visit_mut::visit_impl_item_fn_mut(self, item);
+
+        if let Some(span) = take_marker(&mut item.attrs) {
+            if self.target == Target::Nightly {
+                item.sig.constness = Some(Token![const](span));
+            }
+        }
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_mut::visit_expr_mut(self, expr);
+
+        let label = match expr {
+            Expr::Block(block) => match &block.label {
+                Some(label) if label.name.ident == ALWAYS => label.name.apostrophe,
+                _ => return,
+            },
+            _ => return,
+        };
+
+        if let Expr::Block(mut block) = std::mem::replace(expr, Expr::Verbatim(TokenStream::new()))
+        {
+            *expr = match self.target {
+                Target::Stable => {
+                    block.label = None;
+                    Expr::Block(block)
+                }
+                Target::Nightly => Expr::Const(ExprConst {
+                    attrs: block.attrs,
+                    const_token: Token![const](label),
+                    block: block.block,
+                }),
+            };
+        }
+    }
+
+    fn visit_type_param_mut(&mut self, param: &mut syn::TypeParam) {
+        visit_mut::visit_type_param_mut(self, param);
+        self.bounds(&mut param.bounds);
+
+        if param.bounds.is_empty() {
+            param.colon_token = None;
+        }
+    }
+
+    fn visit_where_clause_mut(&mut self, clause: &mut syn::WhereClause) {
+        visit_mut::visit_where_clause_mut(self, clause);
+
+        // Drop predicates whose only bounds were removed.
+        let predicates = std::mem::take(&mut clause.predicates);
+        clause.predicates = predicates
+            .into_iter()
+            .filter(|pred| !matches!(pred, syn::WherePredicate::Type(ty) if ty.bounds.is_empty()))
+            .collect();
+    }
+
+    fn visit_predicate_type_mut(&mut self, pred: &mut syn::PredicateType) {
+        visit_mut::visit_predicate_type_mut(self, pred);
+        self.bounds(&mut pred.bounds);
+    }
+
+    fn visit_trait_item_type_mut(&mut self, item: &mut syn::TraitItemType) {
+        visit_mut::visit_trait_item_type_mut(self, item);
+        self.bounds(&mut item.bounds);
+
+        if item.bounds.is_empty() {
+            item.colon_token = None;
+        }
+    }
+
+    fn visit_item_trait_mut(&mut self, item: &mut syn::ItemTrait) {
+        visit_mut::visit_item_trait_mut(self, item);
+        self.bounds(&mut item.supertraits);
+
+        if item.supertraits.is_empty() {
+            item.colon_token = None;
+        }
+    }
+
+    fn visit_type_impl_trait_mut(&mut self, ty: &mut syn::TypeImplTrait) {
+        visit_mut::visit_type_impl_trait_mut(self, ty);
+        self.bounds(&mut ty.bounds);
+    }
+
+    fn visit_constraint_mut(&mut self, constraint: &mut syn::Constraint) {
+        visit_mut::visit_constraint_mut(self, constraint);
+        self.bounds(&mut constraint.bounds);
+    }
+}
@@ -0,0 +1,98 @@
+use proc_macro2::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenTree};
+
+/// The placeholder keyword
+pub const KEYWORD: &str = "c0nst";
+
+pub fn is_ident(token: &TokenTree, name: &str) -> bool {
+    matches!(token, TokenTree::Ident(ident) if ident == name)
+}
+
+pub fn is_punct(token: &TokenTree, ch: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == ch)
+}
+
+/// Returns whether the token is `[c0nst]`.
+pub fn is_maybe(token: &TokenTree) -> bool {
+    match token {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => {
+            let mut inner = group.stream().into_iter();
+            matches!((inner.next(), inner.next()), (Some(tt), None) if is_ident(&tt, KEYWORD))
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether the group is `[const]`.
+pub fn is_maybe_const(group: &Group) -> bool {
+    is_bracketed(group, "const")
+}
+
+/// Returns whether the group is `[name]`, such as the body of `#[name]`.
+pub fn is_bracketed(group: &Group, name: &str) -> bool {
+    let mut inner = group.stream().into_iter();
+    group.delimiter() == Delimiter::Bracket
+        && matches!((inner.next(), inner.next()), (Some(tt), None) if is_ident(&tt, name))
+}
+
+/// Returns whether the token is `c0nst` or `[c0nst]`.
+pub fn is_marker(token: &TokenTree) -> bool {
+    is_ident(token, KEYWORD) || is_maybe(token)
+}
+
+/// Returns whether the token can start the qualifiers of a function.
+pub fn is_fn_start(token: &TokenTree) -> bool {
+    ["fn", "unsafe", "async", "extern"]
+        .iter()
+        .any(|name| is_ident(token, name))
+}
+
+/// Returns whether the token is a `c0nst` marking the function after it.
+pub fn is_fn_marker(tokens: &[TokenTree], index: usize) -> bool {
+    is_ident(&tokens[index], KEYWORD) && tokens.get(index + 1).map_or(false, is_fn_start)
+}
+
+/// Returns whether the token is a `c0nst` marking the trait after it.
+pub fn is_trait_marker(tokens: &[TokenTree], index: usize) -> bool {
+    let next = |offset: usize, name: &str| {
+        tokens
+            .get(index + offset)
+            .map_or(false, |tt| is_ident(tt, name))
+    };
+
+    is_ident(&tokens[index], KEYWORD)
+        && (next(1, "trait") || (next(1, "unsafe") && next(2, "trait")))
+}
+
+/// Creates the attribute `#[name]` located at `span`.
+pub fn attribute(name: &str, span: Span) -> [TokenTree; 2] {
+    let mut pound = Punct::new('#', Spacing::Alone);
+    pound.set_span(span);
+
+    let ident = TokenTree::Ident(Ident::new(name, span));
+    let mut group = Group::new(Delimiter::Bracket, ident.into());
+    group.set_span(span);
+
+    [pound.into(), group.into()]
+}
+
+/// Splits a list on the commas outside of `<...>`.
+pub fn split(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (index, tt) in tokens.iter().enumerate() {
+        if is_punct(tt, '<') {
+            depth += 1;
+        } else if is_punct(tt, '>') && !(index > 0 && is_punct(&tokens[index - 1], '-')) {
+            depth = depth.saturating_sub(1);
+        } else if is_punct(tt, ',') && depth == 0 {
+            parts.push(&tokens[start..index]);
+            start = index + 1;
+        }
+    }
+
+    parts.push(&tokens[start..]);
+    parts.retain(|part| !part.is_empty());
+    parts
+}
@@ -0,0 +1,47 @@
+use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// An error reported at a specific token of the input
+#[derive(Clone, Debug)]
+pub struct Error {
+    span: Span,
+    message: String,
+}
+
+impl Error {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Emits `::core::compile_error!("...")` spanned at the offending token.
+    pub fn to_compile_error(&self) -> TokenStream {
+        let mut message = Literal::string(&self.message);
+        message.set_span(self.span);
+
+        let mut group = Group::new(Delimiter::Brace, TokenTree::from(message).into());
+        group.set_span(self.span);
+
+        let tokens: [TokenTree; 7] = [
+            Punct::new(':', Spacing::Joint).into(),
+            Punct::new(':', Spacing::Alone).into(),
+            Ident::new("core", self.span).into(),
+            Punct::new(':', Spacing::Joint).into(),
+            Punct::new(':', Spacing::Alone).into(),
+            Ident::new("compile_error", self.span).into(),
+            Punct::new('!', Spacing::Alone).into(),
+        ];
+
+        let mut output: TokenStream = tokens
+            .into_iter()
+            .map(|mut token| {
+                token.set_span(self.span);
+                token
+            })
+            .collect();
+
+        output.extend(std::iter::once(TokenTree::Group(group)));
+        output
+    }
+}
@@ -45,11 +45,15 @@
 //! }
 //! ```
 //!
+//! Const trait syntax written with the real keyword, such as `const trait`,
+//! `impl const`, `[const]` or `~const`, only compiles on nightly, so it is an
+//! error inside the macro which suggests the `c0nst` form instead.
+//!
 //! ## Destruct Trait Support
 //!
 //! The macro provides special handling for the `Destruct` trait, which is
 //! required in some const contexts. The `Destruct` trait references are
-//! automatically resolved to the fully qualified path `core::marker::Destruct`
+//! automatically resolved to the fully qualified path `::core::marker::Destruct`
 //! on nightly and removed entirely on stable:
 //!
 //! ```rust
@@ -57,31 +61,375 @@
 //!
 //! c0nst::c0nst! {
 //!     c0nst trait MyTrait {
-//!         // nightly → `type: Item: const core::marker::Destruct;`
+//!         // nightly → `type: Item: const ::core::marker::Destruct;`
 //!         // stable → `type: Item;`
 //!         type Item: c0nst Destruct;
 //!     }
 //!
 //!     c0nst trait OtherTrait {
-//!         // nightly → `type: Item: [const] core::marker::Destruct;`
+//!         // nightly → `type: Item: [const] ::core::marker::Destruct;`
 //!         // stable → `type: Item;`
 //!         type Item: [c0nst] Destruct;
 //!     }
 //!
 //!     c0nst trait ComplexTrait {
-//!         // nightly → `type: Item: const Clone + const core::marker::Destruct;`
+//!         // nightly → `type: Item: const Clone + const ::core::marker::Destruct;`
 //!         // stable → `type: Item: Clone;`
 //!         type Item: Clone + c0nst Destruct;
 //!     }
 //! }
 //! ```
+//!
+//! The paths `core::marker::Destruct` and `std::marker::Destruct`, with or
+//! without a leading `::`, are removed on stable as well, and so are aliases
+//! declared with `use core::marker::Destruct as D;` in the same or an
+//! enclosing block. The `use` itself is removed on stable, since it names an
+//! unstable trait. Nightly keeps these paths as written.
+//!
+//! The injected path starts at `::core` with mixed-site hygiene, so a local
+//! module named `core` does not shadow it. Crates which reach `core` through a
+//! facade can set the root with `#![c0nst(root = "::facade::core")]`.
+//!
+//! ### Const Drop
+//!
+//! `impl c0nst Drop for Type` becomes `impl const Drop for Type` on nightly,
+//! which requires `#![feature(const_destruct)]`, and a normal `Drop` impl on
+//! stable. A generic type can only be dropped in a const context if all of its
+//! fields can, so nightly requires the impl to bound each field type by
+//! `[const] Destruct`. `#[c0nst_drop]` on the struct, enum or union derives
+//! these bounds for its `impl c0nst Drop` in the same invocation:
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl, const_destruct))]
+//!
+//! c0nst::c0nst! {
+//!     #[c0nst_drop]
+//!     pub struct Guard<T> {
+//!         value: Option<T>,
+//!     }
+//!
+//!     // nightly → `impl<T> const Drop for Guard<T>
+//!     //            where Option<T>: [const] ::core::marker::Destruct`
+//!     // stable → `impl<T> Drop for Guard<T>`
+//!     impl<T> c0nst Drop for Guard<T> {
+//!         fn drop(&mut self) {}
+//!     }
+//! }
+//! ```
+//!
+//! ## Trait Methods
+//!
+//! Methods of a `c0nst trait` and of an `impl c0nst Trait` are const through
+//! the trait, so marking one of them `c0nst fn` has no further effect: the
+//! marker is dropped with a warning. Since rustc never allows `const fn` in a
+//! trait or a trait impl, `c0nst fn` inside a trait or trait impl which is not
+//! itself `c0nst` is reported as an error.
+//!
+//! Inherent impls have no const form, so `impl c0nst Type { ... }` instead
+//! marks every method in the block as `c0nst fn`. Methods which are already
+//! `const` are left alone, a `c0nst fn` inside the block gets a warning, and
+//! `async fn` is reported as an error since it can never be const.
+//!
+//! ## Function Qualifiers
+//!
+//! `c0nst` can be combined with `async`, `unsafe` and `extern "ABI"`, and may
+//! be written anywhere among them: `unsafe extern "C" c0nst fn` is emitted as
+//! `const unsafe extern "C" fn`, in the order rustc requires. Function pointer
+//! types and functions in `extern` blocks can never be const, so `c0nst` on
+//! them is reported as an error.
+//!
+//! ## Opaque Types and Trait Objects
+//!
+//! `impl c0nst Trait` and `impl [c0nst] Trait` are supported in argument and
+//! return position, where nightly accepts `impl const Trait` and
+//! `impl [const] Trait`:
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+//!
+//! c0nst::c0nst! {
+//!     pub c0nst trait Answer {
+//!         fn answer(&self) -> u32;
+//!     }
+//!
+//!     impl c0nst Answer for u32 {
+//!         fn answer(&self) -> u32 { *self }
+//!     }
+//!
+//!     pub c0nst fn answer() -> impl [c0nst] Answer {
+//!         42u32
+//!     }
+//!
+//!     pub fn check(answer: impl c0nst Answer) -> u32 {
+//!         answer.answer()
+//!     }
+//! }
+//! ```
+//!
+//! Trait objects cannot have const bounds, so `dyn c0nst Trait` and
+//! `dyn [c0nst] Trait` are rejected with an error on both targets.
+//!
+//! ## Const Functions on Stable
+//!
+//! By default, `c0nst fn` loses its `const` on stable. Add
+//! `#![c0nst(stable_const)]` at the start of the invocation to keep `const` on
+//! free functions and inherent methods which can be `const fn` on stable: they
+//! are not `async`, their signature (and impl) has no type parameters, no
+//! `impl Trait` and no `c0nst`/`[c0nst]` bounds, and their body has no method
+//! calls, no calls of associated functions such as `T::default()`, no `for`
+//! loops, no `?` and no macros other than `assert!`, `panic!` and the like.
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+//!
+//! c0nst::c0nst! {
+//!     #![c0nst(stable_const)]
+//!
+//!     // nightly → `pub const fn square(x: u32) -> u32`
+//!     // stable → `pub const fn square(x: u32) -> u32`
+//!     pub c0nst fn square(x: u32) -> u32 {
+//!         x * x
+//!     }
+//! }
+//!
+//! const SIXTEEN: u32 = square(4);
+//! ```
+//!
+//! The analysis only looks at the tokens and does not follow calls of free
+//! functions. A kept function whose body calls a `c0nst fn` that was not kept,
+//! or uses an operator of a trait impl, fails to compile on stable; move such
+//! functions to an invocation without the option.
+//!
+//! A literal `const fn` is always const, so it cannot have `c0nst` or
+//! `[c0nst]` bounds: on stable the bounds would be removed while the function
+//! stays const, and it would fail to compile only there. Such bounds are an
+//! error which suggests `c0nst fn` instead.
+//!
+//! ## Doc Comments
+//!
+//! Doc comments are left untouched by default, so they keep showing `c0nst`.
+//! Add `#![c0nst(docs)]` at the start of the invocation to rewrite standalone
+//! `c0nst` and `[c0nst]` words in doc comments to match the target, using
+//! `~const` on nightlies which expect it. Paths like `c0nst::c0nst!`, compounds
+//! like `c0nst-compatible` and ordinary string literals are never changed. On
+//! stable, a code span holding only `c0nst`, or `c0nst` ending a clause, takes
+//! its whole clause with it, so "Marked `c0nst`, so it stays." becomes "So it
+//! stays.":
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+//!
+//! c0nst::c0nst! {
+//!     #![c0nst(docs)]
+//!
+//!     /// Requires a [c0nst] Default item type.
+//!     ///
+//!     /// nightly → "Requires a [const] Default item type."
+//!     /// stable → "Requires a Default item type."
+//!     pub c0nst trait Answer {
+//!         fn answer() -> u32;
+//!     }
+//! }
+//! ```
+//!
+//! ## Lints
+//!
+//! Code that compiles on stable can still fail on nightly, where `c0nst`
+//! becomes `const`. Add `#![c0nst(lint)]` to get warnings for these mistakes
+//! while developing on stable. For example, a `c0nst fn` that calls a trait
+//! method through a type parameter whose bound is not `[c0nst]`:
+//!
+//! ```rust,ignore
+//! c0nst::c0nst! {
+//!     #![c0nst(lint)]
+//!
+//!     pub c0nst fn make<T: Default>() -> T {
+//!         T::default() // warning: `T` is used in a trait call without a `[c0nst]` bound
+//!     }
+//! }
+//! ```
+//!
+//! The lint also reports constructs in the bodies of `c0nst fn`s and of the
+//! methods of `c0nst trait`s and `impl c0nst` blocks which are not allowed in
+//! a const fn: `for` loops, the `?` operator, `.iter()` and the like,
+//! `Box::new`, and macros which allocate or print, such as `vec!` and
+//! `println!`. Nested items are not checked.
+//!
+//! Markers which are accepted but have no effect are always warned about, such
+//! as a `c0nst fn` inside a `c0nst trait` or a `[c0nst]` bound on a concrete
+//! type like `where u8: [c0nst] Default`.
+//!
+//! Proc macros cannot emit warnings directly, so each warning is reported by
+//! the compiler as the use of a deprecated constant, located at the offending
+//! token. Add `#![deny(deprecated)]` to turn them into errors. The constants
+//! follow the expanded items at module level, and go into the body of the
+//! first function when the macro is used in an impl or a trait. The warnings
+//! of an invocation without a function body, such as an expression, are lost.
+//!
+//! ## Inferred Bounds
+//!
+//! A missing `[c0nst]` is the most common way to break the nightly build.
+//! With `#![c0nst(infer_bounds)]`, every trait bound on a generic parameter or
+//! in the where clause of a `c0nst fn` or an `impl c0nst Trait` is `[c0nst]`
+//! unless it opts out with `?c0nst`:
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+//!
+//! c0nst::c0nst! {
+//!     #![c0nst(infer_bounds)]
+//!
+//!     pub c0nst trait Answer {
+//!         fn answer(&self) -> u32;
+//!     }
+//!
+//!     // nightly → `T: [const] Answer, U: Answer`
+//!     pub c0nst fn first<T: Answer, U: ?c0nst Answer>(t: &T, _u: &U) -> u32 {
+//!         t.answer()
+//!     }
+//! }
+//! ```
+//!
+//! Lifetimes, `?Sized`, higher-ranked bounds, bounds on primitive types and
+//! the marker traits `Sized`, `Copy`, `Send`, `Sync` and `Unpin` are left
+//! alone. So are the standard traits which are rarely const: `Iterator`,
+//! `IntoIterator`, `DoubleEndedIterator`, `ExactSizeIterator`, `Fn`, `FnMut`,
+//! `FnOnce`, `Future`, `Debug`, `Display`, `Hash`, `Hasher`, `Any` and `Error`.
+//! Write `[c0nst]` explicitly where one of them should be const. Any other
+//! trait which has no const form must opt out with `?c0nst`. The methods of an
+//! inherent `impl c0nst` block are `c0nst fn`s, so their bounds are inferred as
+//! well.
+//!
+//! ## Custom Rules
+//!
+//! New const syntax on nightly does not have to wait for a release of this
+//! crate. `#![c0nst(rule = "pattern" => "nightly" / "stable")]` declares a
+//! rewrite rule for one invocation, which replaces the tokens of `pattern`
+//! with `nightly` or `stable` depending on the target:
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+//!
+//! c0nst::c0nst! {
+//!     #![c0nst(rule = "[c0nst] Thunk" => "[const] FnOnce() -> u8" / "FnOnce() -> u8")]
+//!
+//!     // nightly → `F: [const] FnOnce() -> u8`
+//!     // stable → `F: FnOnce() -> u8`
+//!     pub c0nst fn call<F: [c0nst] Thunk>(f: F) -> u8 {
+//!         f()
+//!     }
+//! }
+//! ```
+//!
+//! The stable replacement may be left out to remove the pattern on stable. The
+//! pattern must contain `c0nst` or `[c0nst]`, and the replacements must not
+//! contain the pattern. Declared rules are applied after the built-in rules for
+//! `Destruct` and cannot override them, but before `c0nst` and `[c0nst]` are
+//! rewritten on their own.
+//!
+//! ## Target-Specific Items
+//!
+//! Some items have no common form for both targets. `#[c0nst(nightly_only)]`
+//! keeps an item on nightly and removes it entirely on stable, and
+//! `#[c0nst(stable_only)]` does the opposite. They also apply to statements,
+//! fields, variants and function parameters:
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+//!
+//! c0nst::c0nst! {
+//!     pub struct Counter(u32);
+//!
+//!     impl c0nst Counter {
+//!         // nightly → `const fn get(&self) -> u32`
+//!         #[c0nst(nightly_only)]
+//!         pub fn get(&self) -> u32 {
+//!             self.0
+//!         }
+//!
+//!         // stable → `fn get(&self) -> u32`, which may log
+//!         #[c0nst(stable_only)]
+//!         pub fn get(&self) -> u32 {
+//!             println!("get");
+//!             self.0
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The attributes work on items at any level, including the methods of an
+//! impl block, and are removed from the output.
+//!
+//! Within a body, `c0nst::select!` picks between two implementations, such as
+//! a loop which is allowed in a const fn and a faster iterator chain:
+//!
+//! ```rust
+//! #![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+//!
+//! c0nst::c0nst! {
+//!     pub c0nst fn sum(values: &[u32]) -> u32 {
+//!         c0nst::select! {
+//!             nightly => {
+//!                 let mut total = 0;
+//!                 let mut i = 0;
+//!                 while i < values.len() {
+//!                     total += values[i];
+//!                     i += 1;
+//!                 }
+//!                 total
+//!             },
+//!             stable => { values.iter().sum() },
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Where a statement starts, the statements of the selected branch replace the
+//! invocation, so its `let` bindings stay visible after it. In an expression,
+//! or where a branch ending in an expression is followed by more statements
+//! without a `;`, the branch is kept as a block. At item level, including in impl and trait
+//! bodies, the items of the branch replace the invocation. A branch which is
+//! left out selects nothing.
+//!
+//! ## Parsing Engine
+//!
+//! By default markers are rewritten by matching token patterns, which keeps
+//! the macro free of dependencies but cannot tell a bound from an expression:
+//! a variable named `c0nst` would be rewritten too. Enabling the `syn` feature
+//! parses the input instead, so only `c0nst` in keyword or bound position is
+//! treated as a marker. Both engines produce the same output otherwise.
+//!
+//! ## Nightly Versions
+//!
+//! The nightly syntax for const traits has changed over time. A build script
+//! reads the commit date from `rustc -vV` and picks the syntax that compiler
+//! understands:
+//!
+//! | Nightly                   | Trait                    | Maybe-const bound |
+//! |---------------------------|--------------------------|-------------------|
+//! | before 2025-06-28         | `#[const_trait] trait`   | `~const Trait`    |
+//! | 2025-06-28 to 2025-07-17  | `#[const_trait] trait`   | `[const] Trait`   |
+//! | 2025-07-18 and later      | `const trait`            | `[const] Trait`   |
+//!
+//! The same `c0nst` source therefore builds across this whole window.
+//!
+//! Only the syntax is detected, never the target. Emitting const syntax also
+//! requires `#![feature(const_trait_impl)]` in the calling crate, which a
+//! proc macro cannot see, so nightly output is only ever chosen by the
+//! `nightly` feature.
 
+mod cond;
 mod convert;
+mod error;
+mod options;
 mod tests;
+mod warning;
 
 use proc_macro::TokenStream;
 
-use crate::convert::{Convert, Target};
+use crate::convert::{Context, Convert};
+use crate::error::Error;
+use crate::options::Options;
 
 /// Emits conditionally const code.
 ///
@@ -89,13 +437,56 @@ use crate::convert::{Convert, Target};
 /// Otherwise, it will remove `c0nst` and `[c0nst]` syntax.
 ///
 /// The macro also provides special handling for `Destruct` trait references:
-/// - `c0nst Destruct` becomes `const core::marker::Destruct` (nightly) or is removed (stable)
-/// - `[c0nst] Destruct` becomes `[const] core::marker::Destruct` (nightly) or is removed (stable)
+/// - `c0nst Destruct` becomes `const ::core::marker::Destruct` (nightly) or is removed (stable)
+/// - `[c0nst] Destruct` becomes `[const] ::core::marker::Destruct` (nightly) or is removed (stable)
 ///
-/// Nothing more. Nothing less.
+/// Beyond that, it accepts the `#![c0nst(...)]` options, `#[c0nst_drop]`,
+/// `#[c0nst(nightly_only)]`, `#[c0nst(stable_only)]` and `c0nst::select!`
+/// described in the crate documentation. Misplaced markers are reported as
+/// compile errors.
 #[proc_macro]
 pub fn c0nst(input: TokenStream) -> TokenStream {
-    proc_macro2::TokenStream::from(input)
-        .convert(Target::default())
-        .into()
+    expand(input.into(), Context::default()).into()
+}
+
+/// Emits a keyword only if a `cfg` predicate holds.
+///
+/// The input starts with a header `placeholder => keyword if predicate;`. The
+/// placeholder and its bracketed form `[placeholder]` become `keyword` and
+/// `[keyword]` under `#[cfg(predicate)]`, and are removed otherwise:
+///
+/// ```rust
+/// c0nst::cond_kw! {
+///     a5ync => async if feature = "async";
+///
+///     // `#[cfg(feature = "async")] pub async fn fetch() -> u8 { 1 }`
+///     // `#[cfg(not(feature = "async"))] pub fn fetch() -> u8 { 1 }`
+///     pub a5ync fn fetch() -> u8 {
+///         1
+///     }
+/// }
+/// ```
+///
+/// Every item using the placeholder is emitted once for each case. `c0nst!`
+/// is the preset `c0nst => const`, whose target is selected by this crate's
+/// features, with additional handling for const traits.
+#[proc_macro]
+pub fn cond_kw(input: TokenStream) -> TokenStream {
+    cond::expand(input.into()).into()
+}
+
+fn expand(input: proc_macro2::TokenStream, mut cx: Context) -> proc_macro2::TokenStream {
+    let (options, input) = match Options::parse(input) {
+        Ok(parsed) => parsed,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    cx.options = options;
+
+    let output = input.convert(&mut cx);
+    if cx.errors.is_empty() {
+        return warning::attach(output, &cx.warnings);
+    }
+
+    cx.errors.iter().map(Error::to_compile_error).collect()
 }
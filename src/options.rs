@@ -0,0 +1,124 @@
+use proc_macro2::{Delimiter, Literal, TokenStream, TokenTree};
+
+use crate::convert::{is_path, unquote, Custom};
+use crate::error::Error;
+
+/// Per-invocation options
+///
+/// Options are given as inner attributes at the start of the invocation:
+///
+/// ```text
+/// c0nst::c0nst! {
+///     #![c0nst(docs)]
+///     ...
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Rewrite `c0nst` and `[c0nst]` inside `#[doc]` attributes
+    pub docs: bool,
+
+    /// Keep `const` on stable for functions without const trait bounds
+    pub stable_const: bool,
+
+    /// Warn about code which compiles on stable but not on nightly
+    pub lint: bool,
+
+    /// Treat the bounds of `c0nst fn` and `impl c0nst` headers as `[c0nst]`
+    pub infer_bounds: bool,
+
+    /// The path which injected paths start at instead of `::core`
+    pub root: Option<TokenStream>,
+
+    /// Rewrite rules declared with `rule = "pattern" => "nightly" / "stable"`
+    pub rules: Vec<Custom>,
+}
+
+impl Options {
+    /// Strips the leading `#![c0nst(...)]` attributes from the input.
+    pub fn parse(input: TokenStream) -> Result<(Self, TokenStream), Error> {
+        let mut tokens: Vec<_> = input.into_iter().collect();
+        let mut options = Self::default();
+
+        while let Some(args) = header(&tokens) {
+            options.apply(args?)?;
+            tokens.drain(..3);
+        }
+
+        Ok((options, tokens.into_iter().collect()))
+    }
+
+    fn apply(&mut self, args: TokenStream) -> Result<(), Error> {
+        let tokens: Vec<_> = args.into_iter().collect();
+
+        for arg in tokens.split(|tt| matches!(tt, TokenTree::Punct(p) if p.as_char() == ',')) {
+            match arg {
+                [] => {}
+                [TokenTree::Ident(name)] if name == "docs" => self.docs = true,
+                [TokenTree::Ident(name)] if name == "stable_const" => self.stable_const = true,
+                [TokenTree::Ident(name)] if name == "lint" => self.lint = true,
+                [TokenTree::Ident(name)] if name == "infer_bounds" => self.infer_bounds = true,
+                [TokenTree::Ident(name), TokenTree::Punct(eq), TokenTree::Literal(path)]
+                    if name == "root" && eq.as_char() == '=' =>
+                {
+                    self.root = Some(root(path)?)
+                }
+                [TokenTree::Ident(name), TokenTree::Punct(eq), args @ ..]
+                    if name == "rule" && eq.as_char() == '=' =>
+                {
+                    self.rules.push(Custom::parse(name.span(), args)?)
+                }
+                [first, ..] => {
+                    return Err(Error::new(
+                        first.span(),
+                        format!("unknown c0nst option `{first}`"),
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses the string literal of `root = "::facade::core"`.
+fn root(literal: &Literal) -> Result<TokenStream, Error> {
+    let path = unquote(&literal.to_string()).and_then(|text| text.parse().ok());
+
+    match path {
+        Some(path) if is_path(&path) => Ok(path),
+        _ => Err(Error::new(
+            literal.span(),
+            "expected a path to `core`, such as `root = \"::facade::core\"`",
+        )),
+    }
+}
+
+/// Returns the arguments of a leading `#![c0nst(...)]` attribute.
+fn header(tokens: &[TokenTree]) -> Option<Result<TokenStream, Error>> {
+    let attr = match tokens {
+        [TokenTree::Punct(pound), TokenTree::Punct(bang), TokenTree::Group(attr), ..]
+            if pound.as_char() == '#'
+                && bang.as_char() == '!'
+                && attr.delimiter() == Delimiter::Bracket =>
+        {
+            attr
+        }
+        _ => return None,
+    };
+
+    let mut inner = attr.stream().into_iter();
+    match (inner.next(), inner.next(), inner.next()) {
+        (Some(TokenTree::Ident(name)), ..) if name != "c0nst" => None,
+        (Some(TokenTree::Ident(..)), Some(TokenTree::Group(args)), None)
+            if args.delimiter() == Delimiter::Parenthesis =>
+        {
+            Some(Ok(args.stream()))
+        }
+        (Some(TokenTree::Ident(..)), ..) => Some(Err(Error::new(
+            attr.span(),
+            "expected `#![c0nst(option, ...)]`",
+        ))),
+        _ => None,
+    }
+}
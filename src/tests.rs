@@ -1,12 +1,19 @@
-//! Test suite for the TokenStream-based transformation engine.
+//! Test suite for the transformation engines.
 //!
-//! This module tests the core transformation logic without relying on syn's AST parsing.
+//! Every case runs through each available engine, so the token engine and the
+//! optional `syn` engine are held to the same expectations.
 
 #![cfg(test)]
 
-use proc_macro2::TokenStream;
+mod cond;
+mod golden;
+mod spans;
 
-use crate::{Convert, Target};
+use proc_macro2::{Literal, Span, TokenStream};
+
+use crate::convert::{Context, Engine, Profile, Target};
+use crate::expand;
+use crate::warning::Warning;
 
 #[rstest::rstest]
 #[case::basic_items(
@@ -69,11 +76,6 @@ use crate::{Convert, Target};
     "type ParenType = (impl const Send);",
     "type ParenType = (impl Send);"
 )]
-#[case::trait_object_types(
-    "type TraitObjType = dyn c0nst Send + [c0nst] Sync;",
-    "type TraitObjType = dyn const Send + [const] Sync;",
-    "type TraitObjType = dyn Send + Sync;"
-)]
 #[case::non_type_generic_arguments(
     "fn test<const N: usize>() -> [i32; N] { [0; N] }",
     "fn test<const N: usize>() -> [i32; N] { [0; N] }",
@@ -99,16 +101,26 @@ use crate::{Convert, Target};
     "fn test<const N: usize>() -> [u8; N] { [0; N] }",
     "fn test<const N: usize>() -> [u8; N] { [0; N] }"
 )]
-#[case::bare_fn_with_named_params(
-    "type NamedFn = fn(x: impl c0nst Send, y: &dyn [c0nst] Sync) -> i32;",
-    "type NamedFn = fn(x: impl const Send, y: &dyn [const] Sync) -> i32;",
-    "type NamedFn = fn(x: impl Send, y: &dyn Sync) -> i32;"
-)]
 #[case::impl_without_trait(
     "impl c0nst MyStruct { fn new() -> Self { todo!() } }",
-    "impl const MyStruct { fn new() -> Self { todo!() } }",
+    "impl MyStruct { const fn new() -> Self { todo!() } }",
     "impl MyStruct { fn new() -> Self { todo!() } }"
 )]
+#[case::inherent_impl_propagation(
+    "impl<T> c0nst W<T> { #[inline] pub(crate) unsafe fn a() {} fn b(f: fn()) -> fn() { f } fn c() {} const fn d() {} type X = u8; }",
+    "impl<T> W<T> { #[inline] pub(crate) const unsafe fn a() {} const fn b(f: fn()) -> fn() { f } const fn c() {} const fn d() {} type X = u8; }",
+    "impl<T> W<T> { #[inline] pub(crate) unsafe fn a() {} fn b(f: fn()) -> fn() { f } fn c() {} const fn d() {} type X = u8; }"
+)]
+#[case::inherent_impl_nested(
+    "mod m { impl c0nst W { fn f() { fn inner() {} } } impl W { fn g() {} } }",
+    "mod m { impl W { const fn f() { fn inner() {} } } impl W { fn g() {} } }",
+    "mod m { impl W { fn f() { fn inner() {} } } impl W { fn g() {} } }"
+)]
+#[case::inherent_impl_in_fn_body(
+    "fn outer() { impl c0nst W { fn f() {} } let f = || -> impl c0nst Clone { 1 }; }",
+    "fn outer() { impl W { const fn f() {} } let f = || -> impl const Clone { 1 }; }",
+    "fn outer() { impl W { fn f() {} } let f = || -> impl Clone { 1 }; }"
+)]
 #[case::impl_with_negative_trait(
     "impl !Send for MyType {}",
     "impl !Send for MyType {}",
@@ -124,15 +136,40 @@ use crate::{Convert, Target};
     "#[derive(Debug)] pub const unsafe fn test<'a, const N: usize, T: Clone + const Send>() -> Result<T, String> where T: const Default { Ok(T::default()) }",
     "#[derive(Debug)] pub unsafe fn test<'a, const N: usize, T: Clone + Send>() -> Result<T, String> where T: Default { Ok(T::default()) }"
 )]
-#[case::trait_methods(
-    "trait MyTrait { c0nst fn method(&self) -> i32 { 42 } }",
-    "trait MyTrait { const fn method(&self) -> i32 { 42 } }",
-    "trait MyTrait { fn method(&self) -> i32 { 42 } }"
-)]
-#[case::impl_methods(
-    "impl MyTrait for i32 { c0nst fn method(&self) -> i32 { 42 } }",
-    "impl MyTrait for i32 { const fn method(&self) -> i32 { 42 } }",
-    "impl MyTrait for i32 { fn method(&self) -> i32 { 42 } }"
+#[case::extern_abi(
+    "pub c0nst unsafe extern \"C\" fn f() {}",
+    "pub const unsafe extern \"C\" fn f() {}",
+    "pub unsafe extern \"C\" fn f() {}"
+)]
+#[case::qualifier_order(
+    "unsafe extern \"C\" c0nst fn f() {} unsafe c0nst fn g() {} impl W { pub(crate) extern c0nst fn h() {} }",
+    "const unsafe extern \"C\" fn f() {} const unsafe fn g() {} impl W { pub(crate) const extern fn h() {} }",
+    "unsafe extern \"C\" fn f() {} unsafe fn g() {} impl W { pub(crate) extern fn h() {} }"
+)]
+#[case::plain_fn_pointers(
+    "type F = unsafe extern \"C\" fn(u8); c0nst fn f(g: fn() -> u8) -> u8 { g() }",
+    "type F = unsafe extern \"C\" fn(u8); const fn f(g: fn() -> u8) -> u8 { g() }",
+    "type F = unsafe extern \"C\" fn(u8); fn f(g: fn() -> u8) -> u8 { g() }"
+)]
+#[case::generic_const_impl_methods(
+    "impl<T: Fn()> c0nst MyTrait for T where for<'a> &'a T: Copy { fn method(&self) {} }",
+    "impl<T: Fn()> const MyTrait for T where for<'a> &'a T: Copy { fn method(&self) {} }",
+    "impl<T: Fn()> MyTrait for T where for<'a> &'a T: Copy { fn method(&self) {} }"
+)]
+#[case::inherent_impl_methods(
+    "impl MyType { c0nst fn method(&self) -> i32 { 42 } }",
+    "impl MyType { const fn method(&self) -> i32 { 42 } }",
+    "impl MyType { fn method(&self) -> i32 { 42 } }"
+)]
+#[case::nested_module_items(
+    "mod m { c0nst trait T { fn f(); } impl X { c0nst fn g() {} } }",
+    "mod m { const trait T { fn f(); } impl X { const fn g() {} } }",
+    "mod m { trait T { fn f(); } impl X { fn g() {} } }"
+)]
+#[case::nested_fn_items(
+    "fn outer() { let x = 1; c0nst trait A { fn f(); } impl c0nst A for u8 { fn f() {} } }",
+    "fn outer() { let x = 1; const trait A { fn f(); } impl const A for u8 { fn f() {} } }",
+    "fn outer() { let x = 1; trait A { fn f(); } impl A for u8 { fn f() {} } }"
 )]
 #[case::generic_impl_comprehensive(
     "impl<T: c0nst Clone> c0nst From<T> for MyType<T> where T: [c0nst] Send { fn from(t: T) -> Self { MyType(t) } }",
@@ -209,11 +246,6 @@ use crate::{Convert, Target};
     "trait MyTrait { type AssocType; }",
     "trait MyTrait { type AssocType; }"
 )]
-#[case::trait_method_with_default(
-    "trait MyTrait { c0nst fn method(&self) -> i32 { 42 } }",
-    "trait MyTrait { const fn method(&self) -> i32 { 42 } }",
-    "trait MyTrait { fn method(&self) -> i32 { 42 } }"
-)]
 #[case::lifetime_bounds(
     "fn test<'a, T: 'a>() where T: 'a {}",
     "fn test<'a, T: 'a>() where T: 'a {}",
@@ -265,6 +297,21 @@ use crate::{Convert, Target};
     "impl MyTrait for MyType { type Item = String; }",
     "impl MyTrait for MyType { type Item = String; }"
 )]
+#[case::trait_object_without_const(
+    "type TraitObj<'a> = &'a (dyn Send + Sync);",
+    "type TraitObj<'a> = &'a (dyn Send + Sync);",
+    "type TraitObj<'a> = &'a (dyn Send + Sync);"
+)]
+#[case::trait_object_with_const_arguments(
+    "fn test(x: &dyn Fn(impl c0nst Clone) -> i32) {}",
+    "fn test(x: &dyn Fn(impl const Clone) -> i32) {}",
+    "fn test(x: &dyn Fn(impl Clone) -> i32) {}"
+)]
+#[case::trait_object_before_where_clause(
+    "fn f<T>() -> &'static dyn Send where T: c0nst Clone {}",
+    "fn f<T>() -> &'static dyn Send where T: const Clone {}",
+    "fn f<T>() -> &'static dyn Send where T: Clone {}"
+)]
 #[case::parenthesized_path_args(
     "type FnType = dyn Fn(i32) -> i32;",
     "type FnType = dyn Fn(i32) -> i32;",
@@ -401,16 +448,6 @@ use crate::{Convert, Target};
     "fn test() { let _closure = |x: impl const Send| x; }",
     "fn test() { let _closure = |x: impl Send| x; }"
 )]
-#[case::box_dyn_trait_bounds(
-    "type BoxedTrait = Box<dyn c0nst Send + c0nst Sync>;",
-    "type BoxedTrait = Box<dyn const Send + const Sync>;",
-    "type BoxedTrait = Box<dyn Send + Sync>;"
-)]
-#[case::nested_generic_complex(
-    "type Complex = HashMap<String, Vec<Box<dyn c0nst Send>>>;",
-    "type Complex = HashMap<String, Vec<Box<dyn const Send>>>;",
-    "type Complex = HashMap<String, Vec<Box<dyn Send>>>;"
-)]
 #[case::function_pointer_return_impl(
     "type FnPtr = fn() -> impl c0nst Clone;",
     "type FnPtr = fn() -> impl const Clone;",
@@ -421,11 +458,6 @@ use crate::{Convert, Target};
     "fn test<F>() where F: for<'a> Fn(&'a str) + const Send {}",
     "fn test<F>() where F: for<'a> Fn(&'a str) + Send {}"
 )]
-#[case::raw_pointer_types(
-    "type RawPtr = *const dyn c0nst Send;",
-    "type RawPtr = *const dyn const Send;",
-    "type RawPtr = *const dyn Send;"
-)]
 #[case::async_return_bounds(
     "c0nst async fn test() -> impl c0nst Send + c0nst Future<Output = i32> { async { 42 } }",
     "const async fn test() -> impl const Send + const Future<Output = i32> { async { 42 } }",
@@ -441,16 +473,6 @@ use crate::{Convert, Target};
     "fn test() { let _f = || -> impl const Clone { 42 }; }",
     "fn test() { let _f = || -> impl Clone { 42 }; }"
 )]
-#[case::pin_box_future(
-    "type PinnedFuture = Pin<Box<dyn c0nst Future<Output = String>>>;",
-    "type PinnedFuture = Pin<Box<dyn const Future<Output = String>>>;",
-    "type PinnedFuture = Pin<Box<dyn Future<Output = String>>>;"
-)]
-#[case::trait_object_with_lifetime(
-    "type TraitObj<'a> = &'a (dyn c0nst Send + c0nst Sync);",
-    "type TraitObj<'a> = &'a (dyn const Send + const Sync);",
-    "type TraitObj<'a> = &'a (dyn Send + Sync);"
-)]
 #[case::complex_where_clause_projection(
     "fn test<T>() where T::Output: c0nst Into<String> + c0nst Send {}",
     "fn test<T>() where T::Output: const Into<String> + const Send {}",
@@ -464,44 +486,264 @@ use crate::{Convert, Target};
 #[case::const_block("c0nst { 32 }", "const { 32 }", "{ 32 }")]
 #[case::const_destruct(
     "c0nst trait MyTrait { type Foo: c0nst Destruct; }",
-    "const trait MyTrait { type Foo: const core::marker::Destruct; }",
+    "const trait MyTrait { type Foo: const ::core::marker::Destruct; }",
     "trait MyTrait { type Foo; }"
 )]
 #[case::maybe_const_destruct(
     "c0nst trait MyTrait { type Foo: [c0nst] Destruct; }",
-    "const trait MyTrait { type Foo: [const] core::marker::Destruct; }",
+    "const trait MyTrait { type Foo: [const] ::core::marker::Destruct; }",
     "trait MyTrait { type Foo; }"
 )]
 #[case::trailing_const_destruct(
     "c0nst trait MyTrait { type Foo: Clone + c0nst Destruct; }",
-    "const trait MyTrait { type Foo: Clone + const core::marker::Destruct; }",
+    "const trait MyTrait { type Foo: Clone + const ::core::marker::Destruct; }",
     "trait MyTrait { type Foo: Clone; }"
 )]
 #[case::trailing_maybe_const_destruct(
     "c0nst trait MyTrait { type Foo: Clone + [c0nst] Destruct; }",
-    "const trait MyTrait { type Foo: Clone + [const] core::marker::Destruct; }",
+    "const trait MyTrait { type Foo: Clone + [const] ::core::marker::Destruct; }",
     "trait MyTrait { type Foo: Clone; }"
 )]
 #[case::destruct_bound_single(
     "impl<T: [c0nst] Destruct> MyTrait for T {}",
-    "impl<T: [const] core::marker::Destruct> MyTrait for T {}",
+    "impl<T: [const] ::core::marker::Destruct> MyTrait for T {}",
     "impl<T> MyTrait for T {}"
 )]
 #[case::destruct_bound_multiple_first(
     "impl<T: [c0nst] Destruct + Clone + Copy> MyTrait for T {}",
-    "impl<T: [const] core::marker::Destruct + Clone + Copy> MyTrait for T {}",
+    "impl<T: [const] ::core::marker::Destruct + Clone + Copy> MyTrait for T {}",
     "impl<T: Clone + Copy> MyTrait for T {}"
 )]
 #[case::destruct_bound_multiple_middle(
     "impl<T: Copy + [c0nst] Destruct + Clone> MyTrait for T {}",
-    "impl<T: Copy + [const] core::marker::Destruct + Clone> MyTrait for T {}",
+    "impl<T: Copy + [const] ::core::marker::Destruct + Clone> MyTrait for T {}",
     "impl<T: Copy + Clone> MyTrait for T {}"
 )]
 #[case::destruct_bound_multiple_end(
     "impl<T: Clone + Copy + [c0nst] Destruct> MyTrait for T {}",
-    "impl<T: Clone + Copy + [const] core::marker::Destruct> MyTrait for T {}",
+    "impl<T: Clone + Copy + [const] ::core::marker::Destruct> MyTrait for T {}",
     "impl<T: Clone + Copy> MyTrait for T {}"
 )]
+#[case::const_drop_impl(
+    "struct Guard; impl c0nst Drop for Guard { fn drop(&mut self) {} }",
+    "struct Guard; impl const Drop for Guard { fn drop(&mut self) {} }",
+    "struct Guard; impl Drop for Guard { fn drop(&mut self) {} }"
+)]
+#[case::destruct_paths(
+    "c0nst fn f<T: [c0nst] core::marker::Destruct, U: Clone + c0nst ::std::marker::Destruct>() {}",
+    "const fn f<T: [const] core::marker::Destruct, U: Clone + const ::std::marker::Destruct>() {}",
+    "fn f<T, U: Clone>() {}"
+)]
+#[case::destruct_alias(
+    "use core::marker::Destruct as D; mod m { c0nst fn f<T: [c0nst] D + Copy>() {} } fn g() { use std::marker::Destruct as E; c0nst fn h<T: Copy + [c0nst] E>() {} }",
+    "use core::marker::Destruct as D; mod m { const fn f<T: [const] D + Copy>() {} } fn g() { use std::marker::Destruct as E; const fn h<T: Copy + [const] E>() {} }",
+    "mod m { fn f<T: Copy>() {} } fn g() { fn h<T: Copy>() {} }"
+)]
+#[case::destruct_root(
+    "#![c0nst(root = \"::facade::core\")] c0nst fn f<T: [c0nst] Destruct>() {}",
+    "const fn f<T: [const] ::facade::core::marker::Destruct>() {}",
+    "fn f<T>() {}"
+)]
+#[case::c0nst_drop_struct(
+    "#[c0nst_drop] pub struct G<'a, T, U: Copy> { a: Option<T>, pub(crate) b: (U, u8), c: &'a u8, d: Option<T> } impl<'a, T, U: Copy> c0nst Drop for G<'a, T, U> { fn drop(&mut self) {} }",
+    "pub struct G<'a, T, U: Copy> { a: Option<T>, pub(crate) b: (U, u8), c: &'a u8, d: Option<T> } impl<'a, T, U: Copy> const Drop for G<'a, T, U> where Option<T>: [const] ::core::marker::Destruct, (U, u8): [const] ::core::marker::Destruct { fn drop(&mut self) {} }",
+    "pub struct G<'a, T, U: Copy> { a: Option<T>, pub(crate) b: (U, u8), c: &'a u8, d: Option<T> } impl<'a, T, U: Copy> Drop for G<'a, T, U> { fn drop(&mut self) {} }"
+)]
+#[case::c0nst_drop_enum(
+    "impl<T: Copy> c0nst Drop for E<T> where T: Clone { fn drop(&mut self) {} } #[derive(Debug)] #[c0nst_drop] enum E<T: Copy> where T: Clone { A(T), B { x: [T; 2] }, C = 3 }",
+    "impl<T: Copy> const Drop for E<T> where T: Clone, T: [const] ::core::marker::Destruct, [T; 2]: [const] ::core::marker::Destruct { fn drop(&mut self) {} } #[derive(Debug)] enum E<T: Copy> where T: Clone { A(T), B { x: [T; 2] }, C = 3 }",
+    "impl<T: Copy> Drop for E<T> where T: Clone { fn drop(&mut self) {} } #[derive(Debug)] enum E<T: Copy> where T: Clone { A(T), B { x: [T; 2] }, C = 3 }"
+)]
+#[case::c0nst_drop_renamed_params(
+    "#[c0nst_drop] struct G<'a, T, const N: usize> { a: &'a [T; N], b: Option<T> } impl<'b, U, const M: usize> c0nst Drop for G<'b, U, M> { fn drop(&mut self) {} }",
+    "struct G<'a, T, const N: usize> { a: &'a [T; N], b: Option<T> } impl<'b, U, const M: usize> const Drop for G<'b, U, M> where &'b [U; M]: [const] ::core::marker::Destruct, Option<U>: [const] ::core::marker::Destruct { fn drop(&mut self) {} }",
+    "struct G<'a, T, const N: usize> { a: &'a [T; N], b: Option<T> } impl<'b, U, const M: usize> Drop for G<'b, U, M> { fn drop(&mut self) {} }"
+)]
+#[case::c0nst_drop_path(
+    "#[c0nst_drop] struct G<T>(T::Item, T); impl<U: Iterator> c0nst Drop for self::G<U> { fn drop(&mut self) {} }",
+    "struct G<T>(T::Item, T); impl<U: Iterator> const Drop for self::G<U> where U::Item: [const] ::core::marker::Destruct, U: [const] ::core::marker::Destruct { fn drop(&mut self) {} }",
+    "struct G<T>(T::Item, T); impl<U: Iterator> Drop for self::G<U> { fn drop(&mut self) {} }"
+)]
+#[case::c0nst_drop_concrete(
+    "#[c0nst_drop] struct S(u8, String); impl c0nst Drop for S { fn drop(&mut self) {} }",
+    "struct S(u8, String); impl const Drop for S { fn drop(&mut self) {} }",
+    "struct S(u8, String); impl Drop for S { fn drop(&mut self) {} }"
+)]
+#[case::custom_rule(
+    "#![c0nst(rule = \"c0nst Tuple\" => \"const core::marker::Tuple\" / \"Sized\")] c0nst fn f<T: c0nst Tuple>() {}",
+    "const fn f<T: const core::marker::Tuple>() {}",
+    "fn f<T: Sized>() {}"
+)]
+#[case::custom_rule_removed_on_stable(
+    "#![c0nst(rule = r#\"[c0nst] Tuple +\"# => \"[const] core::marker::Tuple +\")] impl<T: [c0nst] Tuple + Copy> c0nst Tr for T {}",
+    "impl<T: [const] core::marker::Tuple + Copy> const Tr for T {}",
+    "impl<T: Copy> Tr for T {}"
+)]
+#[case::custom_rules_after_destruct(
+    "#![c0nst(rule = \"c0nst Destruct\" => \"const Drop\", rule = \"[c0nst] Fn\" => \"[const] Fn\" / \"Fn\")] c0nst fn f<T: c0nst Destruct, F: [c0nst] Fn()>() {}",
+    "const fn f<T: const ::core::marker::Destruct, F: [const] Fn()>() {}",
+    "fn f<T, F: Fn()>() {}"
+)]
+#[case::nightly_only_items(
+    "#[c0nst(nightly_only)] impl<T: [c0nst] Default> c0nst Tr for T {} #[c0nst(stable_only)] impl<T: Default> Tr for T {} struct S;",
+    "impl<T: [const] Default> const Tr for T {} struct S;",
+    "impl<T: Default> Tr for T {} struct S;"
+)]
+#[case::nightly_only_nested(
+    "impl c0nst W { #[c0nst(nightly_only)] #[inline] fn f() {} fn g() {} } #[c0nst(stable_only)] const N: u8 = { 1 };",
+    "impl W { #[inline] const fn f() {} const fn g() {} }",
+    "impl W { fn g() {} } const N: u8 = { 1 };"
+)]
+#[case::nightly_only_fields(
+    "struct S { a: u8, #[c0nst(nightly_only)] b: Box<dyn Fn(u8, u8)>, c: u8 } enum E { #[c0nst(stable_only)] A(u8, u8), B } fn f(#[c0nst(stable_only)] x: u8, y: u8) { let z = 1; #[c0nst(nightly_only)] let w = 2; }",
+    "struct S { a: u8, b: Box<dyn Fn(u8, u8)>, c: u8 } enum E { B } fn f(y: u8) { let z = 1; let w = 2; }",
+    "struct S { a: u8, c: u8 } enum E { A(u8, u8), B } fn f(x: u8, y: u8) { let z = 1; }"
+)]
+#[case::nightly_only_in_fn_body(
+    "fn f() { #[c0nst(nightly_only)] fn g() {} fn h() {} } fn k() { #[c0nst(nightly_only)] { a(); } b() }",
+    "fn f() { fn g() {} fn h() {} } fn k() { { a(); } b() }",
+    "fn f() { fn h() {} } fn k() { b() }"
+)]
+#[case::select_expression(
+    "c0nst fn f(n: u8) -> u8 { let x = c0nst::select! { nightly => { n + 1 }, stable => { n.wrapping_add(1) } }; x }",
+    "const fn f(n: u8) -> u8 { let x = { n + 1 }; x }",
+    "fn f(n: u8) -> u8 { let x = { n.wrapping_add(1) }; x }"
+)]
+#[case::select_items(
+    "::c0nst::select! { stable => { fn g() {} }, nightly => { c0nst fn g() {} fn h() {} } } struct S;",
+    "const fn g() {} fn h() {} struct S;",
+    "fn g() {} struct S;"
+)]
+#[case::select_missing_branch(
+    "impl c0nst W { c0nst::select! { nightly => { fn f() {} } } fn g() { c0nst::select! { nightly => { a(); } } } }",
+    "impl W { const fn f() {} const fn g() { a(); } }",
+    "impl W { fn g() {} }"
+)]
+#[case::select_statements(
+    "fn f(n: u8) -> u8 { c0nst::select! { nightly => { let x = n + 1; }, stable => { let x = n; } }; if n > 0 { g(); } c0nst::select! { stable => { x * 2 } } }",
+    "fn f(n: u8) -> u8 { let x = n + 1; if n > 0 { g(); } }",
+    "fn f(n: u8) -> u8 { let x = n; if n > 0 { g(); } x * 2 }"
+)]
+#[case::select_expression_statement(
+    "fn f() { c0nst::select! { nightly => { a() } } b(); c0nst::select! { stable => { c() } }; d() }",
+    "fn f() { { a() } b(); d() }",
+    "fn f() { b(); c(); d() }"
+)]
+#[case::where_destruct(
+    "c0nst fn f<T>(t: T) where T: [c0nst] Destruct {} c0nst fn g<T, U>() where T: [c0nst] Destruct, U: Clone {}",
+    "const fn f<T>(t: T) where T: [const] ::core::marker::Destruct {} const fn g<T, U>() where T: [const] ::core::marker::Destruct, U: Clone {}",
+    "fn f<T>(t: T) {} fn g<T, U>() where U: Clone {}"
+)]
+#[case::select_in_groups(
+    "fn f() { g(c0nst::select! { nightly => { 1 } }, 2); let y = 1 + c0nst::select! { stable => { 2 } }; }",
+    "fn f() { g({ 1 }, 2); let y = 1 + {}; }",
+    "fn f() { g({}, 2); let y = 1 + { 2 }; }"
+)]
+#[case::infer_bounds_fn(
+    "#![c0nst(infer_bounds)] c0nst fn f<'a, T: Clone + 'a + ?Sized, U: ?c0nst PartialEq + Copy, const N: usize>(t: &'a T) where T: Default, u8: From<U> {}",
+    "const fn f<'a, T: [const] Clone + 'a + ?Sized, U: PartialEq + Copy, const N: usize>(t: &'a T) where T: [const] Default, u8: From<U> {}",
+    "fn f<'a, T: Clone + 'a + ?Sized, U: PartialEq + Copy, const N: usize>(t: &'a T) where T: Default, u8: From<U> {}"
+)]
+#[case::infer_bounds_impl(
+    "#![c0nst(infer_bounds)] impl<T: core::ops::Add<Output = T> + [c0nst] Clone> c0nst Tr for W<T> where W<T>: Destruct + Default { fn f<U: Clone>() {} } impl<T: Clone> Tr for V<T> {}",
+    "impl<T: [const] core::ops::Add<Output = T> + [const] Clone> const Tr for W<T> where W<T>: [const] ::core::marker::Destruct + [const] Default { fn f<U: Clone>() {} } impl<T: Clone> Tr for V<T> {}",
+    "impl<T: core::ops::Add<Output = T> + Clone> Tr for W<T> where W<T>: Default { fn f<U: Clone>() {} } impl<T: Clone> Tr for V<T> {}"
+)]
+#[case::infer_bounds_inherent(
+    "#![c0nst(infer_bounds)] impl<T: Clone> c0nst W<T> { fn f<U: Default>() {} fn g<F: FnOnce() -> u8>(f: F) -> u8 { f() } }",
+    "impl<T: Clone> W<T> { const fn f<U: [const] Default>() {} const fn g<F: FnOnce() -> u8>(f: F) -> u8 { f() } }",
+    "impl<T: Clone> W<T> { fn f<U: Default>() {} fn g<F: FnOnce() -> u8>(f: F) -> u8 { f() } }"
+)]
+#[case::infer_bounds_std_traits(
+    "#![c0nst(infer_bounds)] c0nst fn f<I: Iterator<Item = u8>, F: Fn(u8), D: core::fmt::Debug + Default, G: [c0nst] FnMut()>() {}",
+    "const fn f<I: Iterator<Item = u8>, F: Fn(u8), D: core::fmt::Debug + [const] Default, G: [const] FnMut()>() {}",
+    "fn f<I: Iterator<Item = u8>, F: Fn(u8), D: core::fmt::Debug + Default, G: FnMut()>() {}"
+)]
+#[case::literal_const_elsewhere(
+    "impl<const N: usize> T for [u8; N] { const M: u8 = 1 + const { 2 }; const fn f() {} }",
+    "impl<const N: usize> T for [u8; N] { const M: u8 = 1 + const { 2 }; const fn f() {} }",
+    "impl<const N: usize> T for [u8; N] { const M: u8 = 1 + const { 2 }; const fn f() {} }"
+)]
+#[case::doc_comments(
+    "#![c0nst(docs)] /// A `[c0nst] Default` bound on a c0nst fn.\n c0nst fn f() {}",
+    "#[doc = \" A `[const] Default` bound on a const fn.\"] const fn f() {}",
+    "#[doc = \" A `Default` bound on a fn.\"] fn f() {}"
+)]
+#[case::doc_comments_paths(
+    "#![c0nst(docs)] /// Expanded by `c0nst::c0nst!`, not c0nsty.\n struct S;",
+    "#[doc = \" Expanded by `c0nst::c0nst!`, not c0nsty.\"] struct S;",
+    "#[doc = \" Expanded by `c0nst::c0nst!`, not c0nsty.\"] struct S;"
+)]
+#[case::doc_comments_nested(
+    "#![c0nst(docs)] c0nst trait T { /** Always\nc0nst. */ fn f(); }",
+    "const trait T { #[doc = \" Always\\nconst. \"] fn f(); }",
+    "trait T { #[doc = \"\"] fn f(); }"
+)]
+#[case::doc_comments_code_spans(
+    "#![c0nst(docs)] /// Marked `c0nst` or `[c0nst]`, so c0nst-compatible and non-c0nst stay.\n fn f() {}",
+    "#[doc = \" Marked `const` or `[const]`, so c0nst-compatible and non-c0nst stay.\"] fn f() {}",
+    "#[doc = \" So c0nst-compatible and non-c0nst stay.\"] fn f() {}"
+)]
+#[case::doc_comments_disabled(
+    "/// A c0nst fn.\n c0nst fn f() {}",
+    "#[doc = \" A c0nst fn.\"] const fn f() {}",
+    "#[doc = \" A c0nst fn.\"] fn f() {}"
+)]
+#[case::stable_const_free_fn(
+    "#![c0nst(stable_const)] pub c0nst fn square(x: u32) -> u32 { x * x }",
+    "pub const fn square(x: u32) -> u32 { x * x }",
+    "pub const fn square(x: u32) -> u32 { x * x }"
+)]
+#[case::stable_const_lifetimes_and_consts(
+    "#![c0nst(stable_const)] c0nst unsafe fn f<'a, const N: usize>(x: &'a [u8; N]) -> &'a u8 { &x[0] }",
+    "const unsafe fn f<'a, const N: usize>(x: &'a [u8; N]) -> &'a u8 { &x[0] }",
+    "const unsafe fn f<'a, const N: usize>(x: &'a [u8; N]) -> &'a u8 { &x[0] }"
+)]
+#[case::stable_const_inherent_method(
+    "#![c0nst(stable_const)] impl<'a> Wrapper<'a> { pub c0nst fn get(&self) -> u32 { self.0 } }",
+    "impl<'a> Wrapper<'a> { pub const fn get(&self) -> u32 { self.0 } }",
+    "impl<'a> Wrapper<'a> { pub const fn get(&self) -> u32 { self.0 } }"
+)]
+#[case::stable_const_const_bounds(
+    "#![c0nst(stable_const)] c0nst fn f<T: [c0nst] Default>() -> T { T::default() }",
+    "const fn f<T: [const] Default>() -> T { T::default() }",
+    "fn f<T: Default>() -> T { T::default() }"
+)]
+#[case::stable_const_type_params(
+    "#![c0nst(stable_const)] c0nst fn f<T: Copy>(x: T) -> T { x }",
+    "const fn f<T: Copy>(x: T) -> T { x }",
+    "fn f<T: Copy>(x: T) -> T { x }"
+)]
+#[case::stable_const_impl_trait(
+    "#![c0nst(stable_const)] c0nst fn f(x: impl Copy) {}",
+    "const fn f(x: impl Copy) {}",
+    "fn f(x: impl Copy) {}"
+)]
+#[case::stable_const_generic_impl(
+    "#![c0nst(stable_const)] impl<T> Wrapper<T> { c0nst fn len(&self) -> usize { 1 } }",
+    "impl<T> Wrapper<T> { const fn len(&self) -> usize { 1 } }",
+    "impl<T> Wrapper<T> { fn len(&self) -> usize { 1 } }"
+)]
+#[case::stable_const_inherent_impl(
+    "#![c0nst(stable_const)] impl c0nst W { fn a() -> u8 { 1 } fn b<T: [c0nst] Default>() -> T { T::default() } }",
+    "impl W { const fn a() -> u8 { 1 } const fn b<T: [const] Default>() -> T { T::default() } }",
+    "impl W { const fn a() -> u8 { 1 } fn b<T: Default>() -> T { T::default() } }"
+)]
+#[case::stable_const_body_checks(
+    "#![c0nst(stable_const)] c0nst fn a(x: u8) -> u8 { assert!(x != 0); let y = [x; 2]; y[0] + W::N } c0nst async fn b() {} c0nst fn c(x: &S) -> S { x.clone() } c0nst fn d() -> u8 { u8::default() } c0nst fn e(x: &[u8]) -> u8 { for _ in x {} 0 } c0nst fn f() -> Vec<u8> { vec![] } c0nst fn g(x: Option<u8>) -> Option<u8> { Some(x?) } c0nst fn h() -> usize { core::mem::size_of::<u8>() }",
+    "const fn a(x: u8) -> u8 { assert!(x != 0); let y = [x; 2]; y[0] + W::N } const async fn b() {} const fn c(x: &S) -> S { x.clone() } const fn d() -> u8 { u8::default() } const fn e(x: &[u8]) -> u8 { for _ in x {} 0 } const fn f() -> Vec<u8> { vec![] } const fn g(x: Option<u8>) -> Option<u8> { Some(x?) } const fn h() -> usize { core::mem::size_of::<u8>() }",
+    "const fn a(x: u8) -> u8 { assert!(x != 0); let y = [x; 2]; y[0] + W::N } async fn b() {} fn c(x: &S) -> S { x.clone() } fn d() -> u8 { u8::default() } fn e(x: &[u8]) -> u8 { for _ in x {} 0 } fn f() -> Vec<u8> { vec![] } fn g(x: Option<u8>) -> Option<u8> { Some(x?) } fn h() -> usize { core::mem::size_of::<u8>() }"
+)]
+#[case::stable_const_trait_impl(
+    "#![c0nst(stable_const)] impl c0nst Trait for u8 { fn f() {} } c0nst trait Trait { fn f(); }",
+    "impl const Trait for u8 { fn f() {} } const trait Trait { fn f(); }",
+    "impl Trait for u8 { fn f() {} } trait Trait { fn f(); }"
+)]
+#[case::string_literals_untouched(
+    "#![c0nst(docs)] c0nst fn f() -> &'static str { \"c0nst fn\" }",
+    "const fn f() -> &'static str { \"c0nst fn\" }",
+    "fn f() -> &'static str { \"c0nst fn\" }"
+)]
 fn test_transformations(
     #[case] input: &str,
     #[case] nightly_expected: &str,
@@ -512,18 +754,474 @@ fn test_transformations(
     let input: TokenStream = input.parse().expect("Failed to parse input");
     eprintln!("Input: {input:#?}");
 
-    // Nightly Output
-    let result = input
-        .clone()
-        .convert(Target::Nightly)
-        .to_string()
+    for &engine in Engine::ALL {
+        for (target, expected) in [(Target::Nightly, &nightly), (Target::Stable, &stable)] {
+            let result = expand(input.clone(), context(target, engine))
+                .to_string()
+                .replace(&[' ', '\n', '\t'][..], "");
+            assert_eq!(&result, expected, "{engine:?} engine on {target:?}");
+        }
+    }
+}
+
+fn context(target: Target, engine: Engine) -> Context {
+    Context {
+        target,
+        engine,
+        profile: Profile::Current,
+        ..Context::default()
+    }
+}
+
+#[rstest::rstest]
+#[case::tilde_bounds(
+    "c0nst fn f<T: [c0nst] Default + c0nst Clone>() {}",
+    Profile::Tilde,
+    "const fn f<T: ~const Default + const Clone>() {}"
+)]
+#[case::tilde_destruct(
+    "c0nst fn f<T: Copy + [c0nst] Destruct>(t: T) {}",
+    Profile::Tilde,
+    "const fn f<T: Copy + ~const ::core::marker::Destruct>(t: T) {}"
+)]
+#[case::tilde_trait(
+    "pub c0nst trait T: [c0nst] Clone { fn f(); }",
+    Profile::Tilde,
+    "#[const_trait] pub trait T: ~const Clone { fn f(); }"
+)]
+#[case::tilde_docs(
+    "#![c0nst(docs)] /// Needs `T: [c0nst] Default` in a c0nst fn.\n c0nst fn f() {}",
+    Profile::Tilde,
+    "#[doc = \" Needs `T: ~const Default` in a const fn.\"] const fn f() {}"
+)]
+#[case::bracket_trait(
+    "/// Docs\n pub c0nst unsafe trait T { fn f(); } c0nst trait U {}",
+    Profile::Bracket,
+    "#[const_trait] #[doc = \" Docs\"] pub unsafe trait T { fn f(); } #[const_trait] trait U {}"
+)]
+#[case::bracket_impl(
+    "impl<T: [c0nst] Clone> c0nst Tr for W<T> { fn f() {} }",
+    Profile::Bracket,
+    "impl<T: [const] Clone> const Tr for W<T> { fn f() {} }"
+)]
+#[case::tilde_custom_rule(
+    "#![c0nst(rule = \"[c0nst] Tuple\" => \"[const] core::marker::Tuple\")] c0nst fn f<T: [c0nst] Tuple>() {}",
+    Profile::Tilde,
+    "const fn f<T: ~const core::marker::Tuple>() {}"
+)]
+#[case::current_trait(
+    "pub c0nst trait T: [c0nst] Clone { fn f(); }",
+    Profile::Current,
+    "pub const trait T: [const] Clone { fn f(); }"
+)]
+fn test_profiles(#[case] input: &str, #[case] profile: Profile, #[case] expected: &str) {
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+
+    for &engine in Engine::ALL {
+        let cx = Context {
+            profile,
+            ..context(Target::Nightly, engine)
+        };
+
+        let result = expand(input.clone(), cx).to_string();
+        assert_eq!(
+            result.replace(&[' ', '\n', '\t'][..], ""),
+            expected.replace(&[' ', '\n', '\t'][..], ""),
+            "{engine:?} engine"
+        );
+    }
+}
+
+const DYN_CONST: &str = "`dyn c0nst` is not supported: trait objects cannot have const bounds";
+const DYN_MAYBE: &str = "`dyn [c0nst]` is not supported: trait objects cannot have const bounds";
+
+#[rstest::rstest]
+#[case::unknown_option("#![c0nst(bogus)] fn f() {}", &["unknown c0nst option `bogus`"])]
+#[case::rule_without_replacement(
+    "#![c0nst(rule = \"c0nst Tuple\")] fn f() {}",
+    &["expected `rule = \"pattern\" => \"nightly\" / \"stable\"`"]
+)]
+#[case::rule_not_a_string(
+    "#![c0nst(rule = \"c0nst Tuple\" => const)] fn f() {}",
+    &["expected a string literal"]
+)]
+#[case::rule_without_marker(
+    "#![c0nst(rule = \"Tuple\" => \"core::marker::Tuple\")] fn f() {}",
+    &["the pattern of a rule must contain `c0nst` or `[c0nst]`"]
+)]
+#[case::rule_recursive(
+    "#![c0nst(rule = \"c0nst Tuple\" => \"Sized\" / \"c0nst Tuple + Sized\")] fn f() {}",
+    &["the replacement of a rule must not contain its pattern"]
+)]
+#[case::root_not_a_path(
+    "#![c0nst(root = \"core + 1\")] struct S;",
+    &["expected a path to `core`, such as `root = \"::facade::core\"`"]
+)]
+#[case::malformed_option(
+    "#![c0nst = \"docs\"] fn f() {}",
+    &["expected `#![c0nst(option, ...)]`"]
+)]
+#[case::trait_object_types(
+    "type TraitObjType = dyn c0nst Send + [c0nst] Sync;",
+    &[DYN_CONST, DYN_MAYBE]
+)]
+#[case::bare_fn_with_named_params(
+    "type NamedFn = fn(x: impl c0nst Send, y: &dyn [c0nst] Sync) -> i32;",
+    &[DYN_MAYBE]
+)]
+#[case::box_dyn_trait_bounds(
+    "type BoxedTrait = Box<dyn c0nst Send + c0nst Sync>;",
+    &[DYN_CONST, DYN_CONST]
+)]
+#[case::nested_generic_complex(
+    "type Complex = HashMap<String, Vec<Box<dyn c0nst Send>>>;",
+    &[DYN_CONST]
+)]
+#[case::raw_pointer_types("type RawPtr = *const dyn c0nst Send;", &[DYN_CONST])]
+#[case::pin_box_future(
+    "type PinnedFuture = Pin<Box<dyn c0nst Future<Output = String>>>;",
+    &[DYN_CONST]
+)]
+#[case::trait_object_with_lifetime(
+    "type TraitObj<'a> = &'a (dyn Send + c0nst Sync);",
+    &[DYN_CONST]
+)]
+#[case::trait_object_in_generics(
+    "fn test<T: AsRef<dyn Send>>(x: Box<dyn Fn() -> u8 + c0nst Send>) {}",
+    &[DYN_CONST]
+)]
+#[case::trait_methods(
+    "trait MyTrait { c0nst fn method(&self) -> i32 { 42 } }",
+    &["`c0nst fn` in a trait requires a `c0nst trait`"]
+)]
+#[case::impl_methods(
+    "impl MyTrait for i32 { c0nst fn method(&self) -> i32 { 42 } }",
+    &["`c0nst fn` in a trait impl requires `impl c0nst Trait`"]
+)]
+#[case::impl_generic_methods(
+    "impl<T> MyTrait for Vec<T> where T: for<'a> Fn(&'a u8) { c0nst fn method() {} }",
+    &["`c0nst fn` in a trait impl requires `impl c0nst Trait`"]
+)]
+#[case::fn_pointer_type(
+    "type F = c0nst fn() -> u8;",
+    &["`c0nst fn` pointer types are not supported: function pointers cannot be const"]
+)]
+#[case::fn_pointer_argument(
+    "c0nst fn f(g: c0nst unsafe extern \"C\" fn()) {}",
+    &["`c0nst fn` pointer types are not supported: function pointers cannot be const"]
+)]
+#[case::fn_pointer_in_groups(
+    "type T = (c0nst fn(), u8); type U = [c0nst unsafe fn(u8); 2]; type V = (c0nst fn());",
+    &[
+        "`c0nst fn` pointer types are not supported: function pointers cannot be const",
+        "`c0nst fn` pointer types are not supported: function pointers cannot be const",
+        "`c0nst fn` pointer types are not supported: function pointers cannot be const",
+    ]
+)]
+#[case::extern_block(
+    "extern \"C\" { c0nst fn f(); fn g(); }",
+    &["`c0nst fn` in an `extern` block is not supported: foreign functions cannot be const"]
+)]
+#[case::literal_const_fn_bound(
+    "const fn f<T: [c0nst] Default>() -> T { T::default() }",
+    &["`[c0nst]` bound on a `const fn`, which stays const on stable where the bound is removed: use `c0nst fn` instead"]
+)]
+#[case::literal_const_fn_where(
+    "impl W { pub const unsafe fn f<T>(t: impl c0nst Into<u8>) -> u8 where T: [c0nst] Default { 1 } }",
+    &["`c0nst` bound on a `const fn`, which stays const on stable where the bound is removed: use `c0nst fn` instead"]
+)]
+#[case::literal_const_trait(
+    "pub const unsafe trait T {} #[const_trait] trait U {}",
+    &[
+        "`const trait` only compiles on nightly: did you mean `c0nst trait`?",
+        "`#[const_trait]` only compiles on nightly: did you mean `c0nst trait`?",
+    ]
+)]
+#[case::literal_const_impl(
+    "impl<const N: usize> const T for [u8; N] {}",
+    &["`impl const` only compiles on nightly: did you mean `impl c0nst`?"]
+)]
+#[case::literal_const_bounds(
+    "c0nst fn f<T: [const] Clone, U: ~const Default>() where T: Copy + const PartialEq {}",
+    &[
+        "`[const]` only compiles on nightly: did you mean `[c0nst]`?",
+        "`~const` only compiles on nightly: did you mean `[c0nst]`?",
+        "`const` bounds only compile on nightly: did you mean `c0nst`?",
+    ]
+)]
+#[case::c0nst_drop_without_impl(
+    "#[c0nst_drop] struct S<T>(T); impl Drop for S<T> { fn drop(&mut self) {} }",
+    &["`#[c0nst_drop]` requires an `impl c0nst Drop for S`"]
+)]
+#[case::c0nst_drop_qualified_self_type(
+    "#[c0nst_drop] struct S<T>(T); impl<T> c0nst Drop for <S<T> as Tr>::Output { fn drop(&mut self) {} }",
+    &["`#[c0nst_drop]` requires an `impl c0nst Drop for S`"]
+)]
+#[case::c0nst_drop_on_fn(
+    "#[c0nst_drop] fn f() {} struct S;",
+    &["`#[c0nst_drop]` must be placed on a struct, enum or union"]
+)]
+#[case::only_unknown(
+    "#[c0nst(beta_only)] fn f() {}",
+    &["expected `#[c0nst(nightly_only)]` or `#[c0nst(stable_only)]`"]
+)]
+#[case::select_unknown_branch(
+    "fn f() { c0nst::select! { beta => { 1 } } }",
+    &["expected `nightly => { ... }` or `stable => { ... }` in `c0nst::select!`"]
+)]
+#[case::select_duplicate_branch(
+    "fn f() { c0nst::select! { stable => { 1 }, stable => { 2 } } }",
+    &["duplicate `stable` branch in `c0nst::select!`"]
+)]
+#[case::opt_out_without_infer_bounds(
+    "c0nst fn f<T: ?c0nst Clone>() {}",
+    &["`?c0nst` requires `#![c0nst(infer_bounds)]`"]
+)]
+#[case::inherent_impl_async(
+    "impl c0nst W { async fn f() {} fn g() {} }",
+    &["`async fn` cannot be const: move it out of the `impl c0nst` block"]
+)]
+fn test_errors(#[case] input: &str, #[case] messages: &[&str]) {
+    let expected: String = messages
+        .iter()
+        .map(|message| format!("::core::compile_error!{{{}}}", Literal::string(message)))
+        .collect::<String>()
         .replace(&[' ', '\n', '\t'][..], "");
-    assert_eq!(result, nightly);
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+
+    for &engine in Engine::ALL {
+        for target in [Target::Nightly, Target::Stable] {
+            let result = expand(input.clone(), context(target, engine))
+                .to_string()
+                .replace(&[' ', '\n', '\t'][..], "");
+            assert_eq!(result, expected, "{engine:?} engine on {target:?}");
+        }
+    }
+}
+
+const MISSING: &str = "missing_c0nst_bound";
+const MISSING_T: &str = "`T` is used in a trait call without a `c0nst` or `[c0nst]` bound, which fails in a `c0nst fn` on nightly";
+const MISSING_U: &str = "`U` is used in a trait call without a `c0nst` or `[c0nst]` bound, which fails in a `c0nst fn` on nightly";
+const REDUNDANT: &str = "redundant_c0nst";
+const REDUNDANT_BOUND: &str = "redundant_c0nst_bound";
+const NON_CONST: &str = "non_const_body";
 
-    // Stable Output
-    let result = input
-        .convert(Target::Stable)
-        .to_string()
+#[rstest::rstest]
+#[case::missing_bound_assoc(
+    "#![c0nst(lint)] c0nst fn f<T: Default>() -> T { T::default() }",
+    &[(MISSING, MISSING_T)]
+)]
+#[case::missing_bound_method(
+    "#![c0nst(lint)] c0nst fn f<T: Clone>(x: &T) -> T { x.clone() }",
+    &[(MISSING, MISSING_T)]
+)]
+#[case::missing_bound_qualified(
+    "#![c0nst(lint)] c0nst fn f<'a, T: Default, U: Clone>(u: &'a U) -> T { let _ = u.clone(); <T as Default>::default() }",
+    &[(MISSING, MISSING_U), (MISSING, MISSING_T)]
+)]
+#[case::missing_bound_once(
+    "#![c0nst(lint)] c0nst fn f<T: Default + Clone>(x: T) -> T { x.clone(); T::default() }",
+    &[(MISSING, MISSING_T)]
+)]
+#[case::missing_bound_inherent(
+    "#![c0nst(lint)] impl c0nst W { fn f<T: Default>() -> T { T::default() } }",
+    &[(MISSING, MISSING_T)]
+)]
+#[case::c0nst_bound(
+    "#![c0nst(lint)] c0nst fn f<T: [c0nst] Default>() -> T { T::default() }",
+    &[]
+)]
+#[case::c0nst_where_bound(
+    "#![c0nst(lint)] c0nst fn f<T>(x: &T) -> T where T: [c0nst] Clone { x.clone() }",
+    &[]
+)]
+#[case::not_called(
+    "#![c0nst(lint)] c0nst fn f<T: Copy>(x: (T, u8)) -> u8 { x.1 + core::u8::MAX }",
+    &[]
+)]
+#[case::plain_fn(
+    "#![c0nst(lint)] fn f<T: Default>() -> T { T::default() }",
+    &[]
+)]
+#[case::lint_disabled(
+    "c0nst fn f<T: Default>() -> T { T::default() }",
+    &[]
+)]
+#[case::non_const_body(
+    "#![c0nst(lint)] c0nst fn f(v: &[u8]) -> Option<u8> { for x in v.iter() {} let b = Box::new(1); Some(g()?) }",
+    &[
+        (NON_CONST, "a `for` loop is not const, which fails in a `c0nst fn` on nightly"),
+        (NON_CONST, "`.iter()` is not const, which fails in a `c0nst fn` on nightly"),
+        (NON_CONST, "`Box::new` is not const, which fails in a `c0nst fn` on nightly"),
+        (NON_CONST, "the `?` operator is not const, which fails in a `c0nst fn` on nightly"),
+    ]
+)]
+#[case::non_const_methods(
+    "#![c0nst(lint)] c0nst trait Tr { fn f(&self) { println!(); } } impl c0nst Tr for u8 { fn f(&self) { let _ = vec![1]; } } impl c0nst W { fn g() { for _ in 0..1 {} } }",
+    &[
+        (NON_CONST, "`println!` is not const, which fails in a `c0nst fn` on nightly"),
+        (NON_CONST, "`vec!` is not const, which fails in a `c0nst fn` on nightly"),
+        (NON_CONST, "a `for` loop is not const, which fails in a `c0nst fn` on nightly"),
+    ]
+)]
+#[case::non_const_nested_items(
+    "#![c0nst(lint)] c0nst fn f<T: ?Sized>() where for<'a> &'a T: Copy { fn g() { for _ in 0..1 {} } impl W { fn h() -> Option<u8> { Some(1)? ; None } } let mut i = 0; while i < 3 { i += 1; } }",
+    &[]
+)]
+#[case::non_const_plain_fn(
+    "#![c0nst(lint)] fn f() { for _ in 0..1 {} } const fn g() {}",
+    &[]
+)]
+#[case::redundant_trait_method(
+    "c0nst trait MyTrait { c0nst fn method(&self) -> i32 { 42 } fn other(&self); }",
+    &[(REDUNDANT, "`c0nst` has no effect: the methods of a `c0nst trait` are already const")]
+)]
+#[case::redundant_nested_trait_method(
+    "fn outer() { c0nst trait A { c0nst fn f(); } }",
+    &[(REDUNDANT, "`c0nst` has no effect: the methods of a `c0nst trait` are already const")]
+)]
+#[case::redundant_impl_method(
+    "impl c0nst MyTrait for i32 { c0nst unsafe fn method(&self) -> i32 { 42 } }",
+    &[(REDUNDANT, "`c0nst` has no effect: the methods of an `impl c0nst Trait` are already const")]
+)]
+#[case::redundant_inherent_method(
+    "impl c0nst W { pub c0nst fn f() {} const fn g() {} }",
+    &[(REDUNDANT, "`c0nst` has no effect: the methods of an `impl c0nst` block are already const")]
+)]
+#[case::redundant_concrete_bounds(
+    "c0nst fn f<T>() where u8: [c0nst] Default, T: [c0nst] Default, bool: c0nst Clone + Copy {}",
+    &[
+        (REDUNDANT_BOUND, "`[c0nst]` has no effect on a bound of the concrete type `u8`"),
+        (REDUNDANT_BOUND, "`c0nst` has no effect on a bound of the concrete type `bool`"),
+    ]
+)]
+#[case::concrete_bound_without_marker(
+    "fn f<T>() where u8: Default, T: Into<u8> {}",
+    &[]
+)]
+fn test_warnings(#[case] input: &str, #[case] warnings: &[(&'static str, &str)]) {
+    let expected: String = warnings
+        .iter()
+        .map(|(name, message)| {
+            Warning::new(Span::call_site(), name, *message)
+                .to_tokens()
+                .to_string()
+        })
+        .collect::<String>()
         .replace(&[' ', '\n', '\t'][..], "");
-    assert_eq!(result, stable);
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+
+    for &engine in Engine::ALL {
+        for target in [Target::Nightly, Target::Stable] {
+            let result = expand(input.clone(), context(target, engine))
+                .to_string()
+                .replace(&[' ', '\n', '\t'][..], "");
+
+            assert!(
+                result.contains(&expected),
+                "{engine:?} engine on {target:?}: {result}"
+            );
+            assert_eq!(result.matches("deprecated").count(), warnings.len());
+        }
+    }
+}
+
+#[rstest::rstest]
+#[case::module(
+    "c0nst fn f() where u8: [c0nst] Default {} struct S;",
+    "fn f() where u8: Default {} struct S; WARNING"
+)]
+#[case::fn_body(
+    "c0nst fn f() where u8: [c0nst] Default { 1 }",
+    "fn f() where u8: Default { WARNING 1 }"
+)]
+#[case::first_fn_body(
+    "fn f(); c0nst fn g() where u8: [c0nst] Default {} fn h() {}",
+    "fn f(); fn g() where u8: Default { WARNING } fn h() {}"
+)]
+#[case::expression("1 + 2", "1 + 2")]
+fn test_warning_positions(#[case] input: &str, #[case] expected: &str) {
+    let warning = Warning::new(
+        Span::call_site(),
+        REDUNDANT_BOUND,
+        "`[c0nst]` has no effect on a bound of the concrete type `u8`",
+    );
+    let expected = expected.replace("WARNING", &warning.to_tokens().to_string());
+    let expected: TokenStream = expected.parse().expect("Failed to parse expected");
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+
+    for &engine in Engine::ALL {
+        let result = expand(input.clone(), context(Target::Stable, engine));
+        assert_eq!(
+            result.to_string(),
+            expected.to_string(),
+            "{engine:?} engine"
+        );
+    }
+}
+
+#[rstest::rstest]
+#[case::code_spans(
+    " Marked `c0nst` or `[c0nst]`, so c0nst-compatible and non-c0nst stay.",
+    " So c0nst-compatible and non-c0nst stay."
+)]
+#[case::last_clause(" Fast, and `c0nst`.", " Fast.")]
+#[case::middle_clause(
+    " Sums the values, `c0nst` where possible, and returns them.",
+    " Sums the values, and returns them."
+)]
+#[case::parentheses(" Returns the sum (`c0nst` on nightly).", " Returns the sum.")]
+#[case::sentence(
+    " Returns `x.y()`. Always c0nst. Never panics.",
+    " Returns `x.y()`. Never panics."
+)]
+#[case::word(
+    " A c0nst fn with a [c0nst] Default bound.",
+    " A fn with a Default bound."
+)]
+fn test_stable_docs(#[case] doc: &str, #[case] expected: &str) {
+    let input = format!(
+        "#![c0nst(docs)] #[doc = {}] fn f() {{}}",
+        Literal::string(doc)
+    );
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+    let expected = format!("# [doc = {}] fn f () {{ }}", Literal::string(expected));
+
+    for &engine in Engine::ALL {
+        let result = expand(input.clone(), context(Target::Stable, engine));
+        assert_eq!(result.to_string(), expected, "{engine:?} engine");
+    }
+}
+
+/// Cases which only the `syn` engine can tell apart from bounds and items.
+#[cfg(feature = "syn")]
+#[rstest::rstest]
+#[case::identifiers(
+    "fn f(c0nst: u8) -> u8 { let v = [c0nst]; v[0] + c0nst }",
+    "fn f(c0nst: u8) -> u8 { let v = [c0nst]; v[0] + c0nst }",
+    "fn f(c0nst: u8) -> u8 { let v = [c0nst]; v[0] + c0nst }"
+)]
+#[case::type_names(
+    "struct c0nst; fn f(x: c0nst, y: Destruct) -> c0nst { x }",
+    "struct c0nst; fn f(x: c0nst, y: Destruct) -> c0nst { x }",
+    "struct c0nst; fn f(x: c0nst, y: Destruct) -> c0nst { x }"
+)]
+#[case::const_block_in_body(
+    "c0nst fn f() -> u8 { let c0nst = 1; let x = c0nst { 2 } + c0nst; x }",
+    "const fn f() -> u8 { let c0nst = 1; let x = const { 2 } + c0nst; x }",
+    "fn f() -> u8 { let c0nst = 1; let x = { 2 } + c0nst; x }"
+)]
+fn test_syntax(#[case] input: &str, #[case] nightly_expected: &str, #[case] stable_expected: &str) {
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+
+    for (target, expected) in [
+        (Target::Nightly, nightly_expected),
+        (Target::Stable, stable_expected),
+    ] {
+        let result = expand(input.clone(), context(target, Engine::Syntax)).to_string();
+        assert_eq!(
+            result.replace(&[' ', '\n', '\t'][..], ""),
+            expected.replace(&[' ', '\n', '\t'][..], ""),
+        );
+    }
 }
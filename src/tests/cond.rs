@@ -0,0 +1,53 @@
+//! Tests for `cond_kw!`.
+
+use proc_macro2::{Literal, TokenStream};
+
+use crate::cond::expand;
+
+#[rstest::rstest]
+#[case::function(
+    "a5ync => async if feature = \"async\"; pub a5ync fn fetch() -> u8 { 1 }",
+    "#[cfg(feature = \"async\")] pub async fn fetch() -> u8 { 1 } \
+     #[cfg(not(feature = \"async\"))] pub fn fetch() -> u8 { 1 }"
+)]
+#[case::bracketed(
+    "m4ybe => unsafe if any(unix, windows); fn f<T: [m4ybe] Tr>() {}",
+    "#[cfg(any(unix, windows))] fn f<T: [unsafe] Tr>() {} \
+     #[cfg(not(any(unix, windows)))] fn f<T: Tr>() {}"
+)]
+#[case::untouched_items(
+    "d3fault => default if feature = \"spec\"; struct S; impl<T> Tr for T { d3fault fn f() {} } const X: S = S {}; fn g() {}",
+    "struct S; \
+     #[cfg(feature = \"spec\")] impl<T> Tr for T { default fn f() {} } \
+     #[cfg(not(feature = \"spec\"))] impl<T> Tr for T { fn f() {} } \
+     const X: S = S {}; fn g() {}"
+)]
+#[case::declaration(
+    "uns4fe => unsafe if all(); #[doc = \"x\"] pub static F: uns4fe fn() = { f }; const fn f() {}",
+    "#[cfg(all())] #[doc = \"x\"] pub static F: unsafe fn() = { f }; \
+     #[cfg(not(all()))] #[doc = \"x\"] pub static F: fn() = { f }; \
+     const fn f() {}"
+)]
+fn test_cond_kw(#[case] input: &str, #[case] expected: &str) {
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+    let result = expand(input)
+        .to_string()
+        .replace(&[' ', '\n', '\t'][..], "");
+    assert_eq!(result, expected.replace(&[' ', '\n', '\t'][..], ""));
+}
+
+#[rstest::rstest]
+#[case::missing_header("pub a5ync fn fetch() {}")]
+#[case::missing_predicate("a5ync => async if; fn f() {}")]
+#[case::missing_arrow("a5ync async if unix; fn f() {}")]
+fn test_cond_kw_errors(#[case] input: &str) {
+    let message = "expected `placeholder => keyword if predicate;`, such as \
+                   `a5ync => async if feature = \"async\";`";
+    let expected = format!("::core::compile_error!{{{}}}", Literal::string(message));
+
+    let input: TokenStream = input.parse().expect("Failed to parse input");
+    let result = expand(input)
+        .to_string()
+        .replace(&[' ', '\n', '\t'][..], "");
+    assert_eq!(result, expected.replace(&[' ', '\n', '\t'][..], ""));
+}
@@ -0,0 +1,110 @@
+//! Golden-file tests for the transformation engine.
+//!
+//! Every `tests/fixtures/NAME.rs` is converted by each engine for both targets
+//! and compared against `NAME.nightly.rs` and `NAME.stable.rs`. The expected
+//! files are compared as tokens, so they may be formatted freely. Run the tests
+//! with `C0NST_BLESS=1` to (re)generate the expected files, which are
+//! formatted with `rustfmt`.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use proc_macro2::TokenStream;
+
+use crate::convert::{Context, Engine, Profile, Target};
+use crate::expand;
+
+/// Environment variable which enables bless mode.
+const BLESS: &str = "C0NST_BLESS";
+
+/// Returns the input fixtures, sorted by name.
+fn fixtures() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .expect("failed to read fixtures directory")
+        .map(|entry| entry.expect("failed to read fixture").path())
+        .filter(|path| {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            name.ends_with(".rs") && name.matches('.').count() == 1
+        })
+        .collect();
+
+    paths.sort();
+    paths
+}
+
+/// Formats an expected file with `rustfmt`, which must be installed to bless.
+fn rustfmt(output: &TokenStream) -> String {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run rustfmt");
+
+    let mut stdin = child.stdin.take().expect("failed to open rustfmt stdin");
+    stdin
+        .write_all(output.to_string().as_bytes())
+        .expect("failed to write to rustfmt");
+    drop(stdin);
+
+    let formatted = child.wait_with_output().expect("failed to run rustfmt");
+    assert!(formatted.status.success(), "rustfmt failed");
+    String::from_utf8(formatted.stdout).expect("rustfmt wrote invalid UTF-8")
+}
+
+#[test]
+fn test_fixtures() {
+    let bless = std::env::var_os(BLESS).is_some();
+    let mut failures = Vec::new();
+
+    for path in fixtures() {
+        let source = fs::read_to_string(&path).expect("failed to read fixture");
+        let input: TokenStream = source.parse().expect("failed to parse fixture");
+
+        for (target, suffix) in [(Target::Nightly, "nightly"), (Target::Stable, "stable")] {
+            for &engine in Engine::ALL {
+                let cx = Context {
+                    target,
+                    engine,
+                    profile: Profile::Current,
+                    ..Context::default()
+                };
+
+                let output = expand(input.clone(), cx);
+                let expected = path.with_extension(format!("{suffix}.rs"));
+
+                if bless {
+                    // Every engine must agree, so bless from the first.
+                    fs::write(&expected, rustfmt(&output)).expect("failed to bless fixture");
+                    break;
+                }
+
+                let text = match fs::read_to_string(&expected) {
+                    Ok(text) => text,
+                    Err(..) => {
+                        failures.push(format!("{} is missing", expected.display()));
+                        continue;
+                    }
+                };
+
+                let tokens: TokenStream = text.parse().expect("failed to parse expected file");
+                if tokens.to_string() != output.to_string() {
+                    failures.push(format!(
+                        "{} differs from the {engine:?} engine output:\n{output}",
+                        expected.display()
+                    ));
+                }
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{}\n\nrerun with {BLESS}=1 to update the expected files",
+        failures.join("\n\n")
+    );
+}
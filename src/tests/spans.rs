@@ -0,0 +1,75 @@
+//! Span tests for IDE support.
+//!
+//! Rust-analyzer maps expanded tokens back to the source through their spans,
+//! so every identifier in the output must carry a span from the input. Tokens
+//! which replace a marker point at that marker.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use proc_macro2::{TokenStream, TokenTree};
+
+use crate::convert::{Context, Engine, Target};
+use crate::expand;
+
+const INPUTS: &[&str] = &[
+    "c0nst trait T: [c0nst] Clone { fn f(&self) -> impl c0nst Into<u8>; }",
+    "impl<T: [c0nst] Destruct + Clone> c0nst T for W<T> where T: c0nst Default { c0nst fn f() {} }",
+    "impl W { c0nst fn get(&self) -> u8 { c0nst { 1 } } }",
+    "#![c0nst(docs)] /// A [c0nst] bound.\n c0nst fn f<T: [c0nst] Default>() -> T { T::default() }",
+    "#![c0nst(stable_const)] pub c0nst fn square(x: u32) -> u32 { x * x }",
+];
+
+/// Collects the spans of every token, identified by their byte range.
+fn collect(stream: TokenStream, spans: &mut HashSet<String>) {
+    for token in stream {
+        spans.insert(format!("{:?}", token.span()));
+        if let TokenTree::Group(group) = token {
+            collect(group.stream(), spans);
+        }
+    }
+}
+
+/// Collects the identifiers whose span does not come from the input.
+fn check(stream: TokenStream, input: &HashSet<String>, failures: &mut Vec<String>) {
+    for token in stream {
+        match token {
+            TokenTree::Group(group) => check(group.stream(), input, failures),
+            TokenTree::Ident(ident) if !input.contains(&format!("{:?}", ident.span())) => {
+                failures.push(format!("`{ident}` has the span {:?}", ident.span()))
+            }
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_spans() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let fixtures = ["rfc.rs", "destruct.rs"]
+        .iter()
+        .map(|name| fs::read_to_string(dir.join(name)).expect("failed to read fixture"));
+
+    let mut failures = Vec::new();
+
+    for source in fixtures.chain(INPUTS.iter().map(|s| s.to_string())) {
+        let input: TokenStream = source.parse().expect("failed to parse input");
+        let mut known = HashSet::new();
+        collect(input.clone(), &mut known);
+
+        for &engine in Engine::ALL {
+            for target in [Target::Nightly, Target::Stable] {
+                let cx = Context {
+                    target,
+                    engine,
+                    ..Context::default()
+                };
+
+                check(expand(input.clone(), cx), &known, &mut failures);
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
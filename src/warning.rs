@@ -0,0 +1,141 @@
+use proc_macro2::{Delimiter, Group, Literal, Span, TokenStream, TokenTree};
+
+use crate::convert::items;
+
+/// Items which only appear in a module or a block, never in an impl or trait
+const MODULE_ITEMS: &[&str] = &[
+    "trait",
+    "impl",
+    "struct",
+    "enum",
+    "union",
+    "mod",
+    "use",
+    "static",
+    "macro_rules",
+];
+
+/// A warning reported at a specific token of the input
+///
+/// Proc macros cannot emit warnings on stable, so a warning is emitted as the
+/// use of a deprecated constant whose note is the message. The compiler then
+/// reports `use of deprecated constant` at the offending token.
+#[derive(Clone, Debug)]
+pub struct Warning {
+    span: Span,
+    name: &'static str,
+    message: String,
+}
+
+impl Warning {
+    /// Creates a warning; `name` identifies the kind of warning.
+    pub fn new(span: Span, name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            name,
+            message: message.into(),
+        }
+    }
+
+    /// Emits an anonymous constant which uses the deprecated constant `name`.
+    pub fn to_tokens(&self) -> TokenStream {
+        let source = format!(
+            "const _: () = {{
+                #[deprecated(note = {note})]
+                #[allow(non_upper_case_globals)]
+                const {name}: () = ();
+                {name}
+            }};",
+            note = Literal::string(&self.message),
+            name = self.name,
+        );
+
+        let tokens: TokenStream = source.parse().expect("valid warning");
+        respan(tokens, self.span)
+    }
+}
+
+/// Keywords which may come before the keyword of an item
+const QUALIFIERS: &[&str] = &["pub", "const", "async", "unsafe", "auto", "default"];
+
+/// Adds the warnings to the output of an invocation.
+///
+/// Each warning is an anonymous `const` item, which is only allowed in a
+/// module or a block. If the output contains an item which only appears
+/// there, the warnings follow it. Otherwise the invocation may be in an impl,
+/// a trait or an expression, so the warnings go into the body of its first
+/// function instead, and are dropped if it has none.
+pub fn attach(output: TokenStream, warnings: &[Warning]) -> TokenStream {
+    if warnings.is_empty() {
+        return output;
+    }
+
+    let warnings: TokenStream = warnings.iter().map(Warning::to_tokens).collect();
+    let mut tokens: Vec<_> = output.into_iter().collect();
+
+    if items(&tokens).into_iter().any(is_module_item) {
+        tokens.extend(warnings);
+        return tokens.into_iter().collect();
+    }
+
+    let mut start = 0;
+    for item in items(&tokens) {
+        let end = start + item.len();
+        let body = match item.last() {
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => Some(g),
+            _ => None,
+        };
+
+        if let Some(body) = body.filter(|_| item.iter().any(|tt| is_ident(tt, "fn"))) {
+            let mut stream = warnings;
+            stream.extend(body.stream());
+
+            let mut group = Group::new(Delimiter::Brace, stream);
+            group.set_span(body.span());
+            tokens[end - 1] = group.into();
+            break;
+        }
+
+        start = end;
+    }
+
+    tokens.into_iter().collect()
+}
+
+/// Returns whether the item only appears in a module or a block.
+fn is_module_item(item: &[TokenTree]) -> bool {
+    let keyword = item.iter().enumerate().find_map(|(index, tt)| match tt {
+        TokenTree::Ident(ident) if !QUALIFIERS.iter().any(|kw| ident == kw) => Some((index, ident)),
+        _ => None,
+    });
+
+    match keyword {
+        Some((index, ident)) if ident == "extern" => item
+            .get(index + 1)
+            .map_or(false, |tt| is_ident(tt, "crate")),
+        Some((_, ident)) => MODULE_ITEMS.iter().any(|kw| ident == kw),
+        None => false,
+    }
+}
+
+fn is_ident(token: &TokenTree, name: &str) -> bool {
+    matches!(token, TokenTree::Ident(ident) if ident == name)
+}
+
+/// Sets the span of every token, so the warning points at the input.
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                TokenTree::Group(respanned)
+            }
+            mut tt => {
+                tt.set_span(span);
+                tt
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,15 @@
+const trait MyTrait {
+    type Item: const ::core::marker::Destruct;
+}
+const trait OtherTrait {
+    type Item: [const] ::core::marker::Destruct;
+}
+const trait ComplexTrait {
+    type Item: Clone + const ::core::marker::Destruct;
+}
+impl<T: Copy + [const] ::core::marker::Destruct + Clone> const MyTrait for Wrapper<T> {
+    type Item = T;
+}
+const fn consume<T: [const] ::core::marker::Destruct>(value: T) {
+    let _ = value;
+}
@@ -0,0 +1,21 @@
+// `Destruct` bounds in every position handled by the rewriting rules.
+
+c0nst trait MyTrait {
+    type Item: c0nst Destruct;
+}
+
+c0nst trait OtherTrait {
+    type Item: [c0nst] Destruct;
+}
+
+c0nst trait ComplexTrait {
+    type Item: Clone + c0nst Destruct;
+}
+
+impl<T: Copy + [c0nst] Destruct + Clone> c0nst MyTrait for Wrapper<T> {
+    type Item = T;
+}
+
+c0nst fn consume<T: [c0nst] Destruct>(value: T) {
+    let _ = value;
+}
@@ -0,0 +1,15 @@
+trait MyTrait {
+    type Item;
+}
+trait OtherTrait {
+    type Item;
+}
+trait ComplexTrait {
+    type Item: Clone;
+}
+impl<T: Copy + Clone> MyTrait for Wrapper<T> {
+    type Item = T;
+}
+fn consume<T>(value: T) {
+    let _ = value;
+}
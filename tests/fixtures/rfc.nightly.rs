@@ -0,0 +1,18 @@
+pub const trait Default {
+    fn default() -> Self;
+}
+impl const Default for () {
+    fn default() -> Self {}
+}
+pub struct Thing<T>(pub T);
+impl<T: [const] Default> const Default for Thing<T> {
+    fn default() -> Self {
+        Self(T::default())
+    }
+}
+pub const fn default<T: [const] Default>() -> T {
+    T::default()
+}
+pub fn compile_time_default<T: const Default>() -> T {
+    const { T::default() }
+}
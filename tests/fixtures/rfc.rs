@@ -0,0 +1,25 @@
+// The canonical example from the crate documentation, derived from the RFC.
+
+pub c0nst trait Default {
+    fn default() -> Self;
+}
+
+impl c0nst Default for () {
+    fn default() -> Self {}
+}
+
+pub struct Thing<T>(pub T);
+
+impl<T: [c0nst] Default> c0nst Default for Thing<T> {
+    fn default() -> Self {
+        Self(T::default())
+    }
+}
+
+pub c0nst fn default<T: [c0nst] Default>() -> T {
+    T::default()
+}
+
+pub fn compile_time_default<T: c0nst Default>() -> T {
+    c0nst { T::default() }
+}
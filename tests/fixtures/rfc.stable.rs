@@ -0,0 +1,20 @@
+pub trait Default {
+    fn default() -> Self;
+}
+impl Default for () {
+    fn default() -> Self {}
+}
+pub struct Thing<T>(pub T);
+impl<T: Default> Default for Thing<T> {
+    fn default() -> Self {
+        Self(T::default())
+    }
+}
+pub fn default<T: Default>() -> T {
+    T::default()
+}
+pub fn compile_time_default<T: Default>() -> T {
+    {
+        T::default()
+    }
+}
@@ -0,0 +1,23 @@
+//! Compile tests for the expanded code.
+//!
+//! Cases under `tests/ui/pass` must compile and cases under `tests/ui/fail`
+//! must fail with the recorded errors for both targets. Cases under
+//! `tests/ui/nightly` and `tests/ui/stable` only run for the matching target,
+//! so run the suite twice: `cargo test` and `cargo +nightly test --features
+//! nightly`. The stable diagnostics mention the channel, so `tests/ui/stable`
+//! is skipped on a nightly compiler. Set `TRYBUILD=overwrite` to regenerate
+//! the `.stderr` files.
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+
+    if cfg!(feature = "nightly") {
+        t.pass("tests/ui/nightly/pass/*.rs");
+        t.compile_fail("tests/ui/nightly/fail/*.rs");
+    } else if !cfg!(c0nst_nightly_rustc) {
+        t.compile_fail("tests/ui/stable/fail/*.rs");
+    }
+}
@@ -0,0 +1,11 @@
+c0nst::c0nst! {
+    pub c0nst trait Answer {
+        fn answer(&self) -> u32;
+    }
+
+    pub fn answer(value: &dyn [c0nst] Answer) -> u32 {
+        value.answer()
+    }
+}
+
+fn main() {}
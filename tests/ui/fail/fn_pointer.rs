@@ -0,0 +1,10 @@
+c0nst::c0nst! {
+    pub type Callback = c0nst fn(u32) -> u32;
+    pub type Pair = (c0nst fn(), u8);
+
+    extern "C" {
+        c0nst fn abs(x: i32) -> i32;
+    }
+}
+
+fn main() {}
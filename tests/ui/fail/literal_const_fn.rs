@@ -0,0 +1,7 @@
+c0nst::c0nst! {
+    pub const fn make<T: [c0nst] Default>() -> T {
+        T::default()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+c0nst::c0nst! {
+    pub const trait Answer {
+        fn answer(&self) -> u32;
+    }
+
+    pub c0nst fn answer<T: [const] Answer>(value: &T) -> u32 {
+        value.answer()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+c0nst::c0nst! {
+    pub trait Answer {
+        c0nst fn answer(&self) -> u32;
+    }
+
+    impl Answer for u32 {
+        c0nst fn answer(&self) -> u32 {
+            *self
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+// The errors of the expanded code point at the original tokens.
+c0nst::c0nst! {
+    pub c0nst trait Answer {
+        fn answer(&self) -> u32;
+    }
+
+    impl c0nst Answer for Nowhere {
+        fn answer(&self) -> u32 {
+            1
+        }
+    }
+
+    pub c0nst fn ask<T: [c0nst] Answer + c0nst Missing>(value: &T) -> u32 {
+        value.answer() + undefined
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#![feature(const_trait_impl)]
+
+// Compiles on stable, but the bound needs `[c0nst]` to be callable on nightly.
+c0nst::c0nst! {
+    pub c0nst trait Compute {
+        fn calculate(&self) -> u32;
+    }
+
+    pub c0nst fn twice<T: Compute>(value: &T) -> u32 {
+        value.calculate() * 2
+    }
+}
+
+fn main() {}
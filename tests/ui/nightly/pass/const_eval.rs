@@ -0,0 +1,42 @@
+#![feature(const_trait_impl)]
+
+c0nst::c0nst! {
+    pub c0nst trait Compute {
+        fn calculate(&self) -> u32;
+    }
+
+    impl c0nst Compute for u32 {
+        fn calculate(&self) -> u32 {
+            *self * 2
+        }
+    }
+
+    pub c0nst fn twice<T: [c0nst] Compute>(value: &T) -> u32 {
+        value.calculate() * 2
+    }
+
+    pub fn compile_time<T: c0nst Compute>(value: &T) -> u32 {
+        value.calculate()
+    }
+
+    pub struct Meters(pub u32);
+
+    impl c0nst Meters {
+        pub fn new(value: u32) -> Self {
+            Self(value)
+        }
+
+        pub fn doubled(&self) -> u32 {
+            self.0.calculate()
+        }
+    }
+}
+
+const VALUE: u32 = twice(&21u32);
+const DOUBLED: u32 = Meters::new(4).doubled();
+
+fn main() {
+    assert_eq!(VALUE, 84);
+    assert_eq!(compile_time(&1u32), 2);
+    assert_eq!(DOUBLED, 8);
+}
@@ -0,0 +1,22 @@
+c0nst::cond_kw! {
+    uns4fe => unsafe if all();
+
+    pub uns4fe fn enabled() -> u8 {
+        1
+    }
+}
+
+mod disabled {
+    c0nst::cond_kw! {
+        uns4fe => unsafe if any();
+
+        pub uns4fe fn disabled() -> u8 {
+            2
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(unsafe { enabled() }, 1);
+    assert_eq!(disabled::disabled(), 2);
+}
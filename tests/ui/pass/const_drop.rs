@@ -0,0 +1,43 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl, const_destruct))]
+
+c0nst::c0nst! {
+    #[c0nst_drop]
+    pub struct Guard<T> {
+        value: Option<T>,
+        count: u32,
+    }
+
+    impl<T> c0nst Drop for Guard<T> {
+        fn drop(&mut self) {}
+    }
+
+    // The impl renames the parameters and names the type by a path.
+    #[c0nst_drop]
+    pub struct Pair<'a, A> {
+        first: &'a A,
+        second: Option<A>,
+    }
+
+    impl<'b, B> c0nst Drop for self::Pair<'b, B> {
+        fn drop(&mut self) {}
+    }
+
+    pub c0nst fn release<T: [c0nst] Destruct>(guard: Guard<T>) -> u32 {
+        guard.count
+    }
+}
+
+#[cfg(feature = "nightly")]
+const RELEASED: u32 = release(Guard { value: Some(1u8), count: 2 });
+
+fn main() {
+    #[cfg(feature = "nightly")]
+    assert_eq!(RELEASED, 2);
+
+    let guard = Guard { value: Some(String::new()), count: 3 };
+    assert!(guard.value.is_some());
+    assert_eq!(release(guard), 3);
+
+    let pair = Pair { first: &1, second: Some(2) };
+    assert_eq!(*pair.first + pair.second.unwrap_or(0), 3);
+}
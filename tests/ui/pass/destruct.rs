@@ -0,0 +1,24 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl, const_destruct))]
+
+c0nst::c0nst! {
+    pub c0nst trait Consume {
+        type Item: Clone + [c0nst] Destruct;
+
+        fn consume(item: Self::Item);
+    }
+
+    impl c0nst Consume for u8 {
+        type Item = u8;
+
+        fn consume(_item: u8) {}
+    }
+
+    pub c0nst fn drop_all<T: Copy + [c0nst] Destruct>(values: [T; 2]) {
+        let _ = values;
+    }
+}
+
+fn main() {
+    u8::consume(1);
+    drop_all([1u8, 2]);
+}
@@ -0,0 +1,27 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl, const_destruct))]
+
+c0nst::c0nst! {
+    use core::marker::Destruct as D;
+
+    pub c0nst fn first<T: Copy + [c0nst] D>(values: [T; 2]) -> T {
+        values[0]
+    }
+
+    pub c0nst fn last<T: Copy + [c0nst] ::core::marker::Destruct>(values: [T; 2]) -> T {
+        values[1]
+    }
+
+    pub fn nested() -> u8 {
+        c0nst fn pick<T: [c0nst] D + Copy>(value: T) -> T {
+            value
+        }
+
+        pick(3u8)
+    }
+}
+
+fn main() {
+    assert_eq!(first([1u8, 2]), 1);
+    assert_eq!(last([1u8, 2]), 2);
+    assert_eq!(nested(), 3);
+}
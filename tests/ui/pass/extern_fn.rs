@@ -0,0 +1,21 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    #![c0nst(stable_const)]
+
+    pub unsafe extern "C" c0nst fn add(a: u32, b: u32) -> u32 {
+        a + b
+    }
+
+    pub c0nst extern "C" fn double(a: u32) -> u32 {
+        unsafe { add(a, a) }
+    }
+}
+
+const SIX: u32 = double(3);
+
+fn main() {
+    let f: unsafe extern "C" fn(u32, u32) -> u32 = add;
+    assert_eq!(unsafe { f(1, 2) }, 3);
+    assert_eq!(SIX, 6);
+}
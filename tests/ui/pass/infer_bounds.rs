@@ -0,0 +1,41 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    #![c0nst(infer_bounds)]
+
+    pub c0nst trait Answer {
+        fn answer(&self) -> u32;
+    }
+
+    impl c0nst Answer for u32 {
+        fn answer(&self) -> u32 {
+            *self
+        }
+    }
+
+    pub struct Pair<T>(pub T, pub T);
+
+    impl<T: Answer> c0nst Answer for Pair<T> {
+        fn answer(&self) -> u32 {
+            self.0.answer() + self.1.answer()
+        }
+    }
+
+    impl<T: Copy> c0nst Pair<T> {
+        pub fn sum(&self) -> u32
+        where
+            T: Answer,
+        {
+            self.answer()
+        }
+    }
+
+    pub c0nst fn total<T: Answer, U: ?c0nst Answer>(t: &T, _u: &U) -> u32 {
+        t.answer()
+    }
+}
+
+fn main() {
+    assert_eq!(Pair(1u32, 2u32).sum(), 3);
+    assert_eq!(total(&5u32, &Pair(1u32, 1u32)), 5);
+}
@@ -0,0 +1,26 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl, const_destruct))]
+
+mod facade {
+    pub use core;
+}
+
+c0nst::c0nst! {
+    pub mod core {}
+
+    pub c0nst fn first<T: Copy + [c0nst] Destruct>(values: [T; 2]) -> T {
+        values[0]
+    }
+}
+
+c0nst::c0nst! {
+    #![c0nst(root = "crate::facade::core")]
+
+    pub c0nst fn last<T: Copy + [c0nst] Destruct>(values: [T; 2]) -> T {
+        values[1]
+    }
+}
+
+fn main() {
+    assert_eq!(first([1u8, 2]), 1);
+    assert_eq!(last([1u8, 2]), 2);
+}
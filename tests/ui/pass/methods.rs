@@ -0,0 +1,36 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    pub c0nst trait Answer {
+        c0nst fn answer(&self) -> u32;
+
+        fn double(&self) -> u32 {
+            self.answer() * 2
+        }
+    }
+
+    impl c0nst Answer for u32 {
+        c0nst fn answer(&self) -> u32 {
+            *self
+        }
+    }
+
+    pub struct Wrapper(pub u32);
+
+    impl Wrapper {
+        pub c0nst fn get(&self) -> u32 {
+            self.0.double()
+        }
+    }
+
+    impl c0nst Wrapper {
+        pub fn half(&self) -> u32 {
+            self.0 / 2
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(Wrapper(21).get(), 42);
+    assert_eq!(Wrapper(42).half(), 21);
+}
@@ -0,0 +1,29 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    pub c0nst trait Answer {
+        fn answer(&self) -> u32;
+    }
+
+    impl c0nst Answer for u32 {
+        fn answer(&self) -> u32 {
+            *self
+        }
+    }
+}
+
+pub struct W;
+
+impl W {
+    // The redundant bound warns from inside an impl block.
+    c0nst::c0nst! {
+        pub c0nst fn get(&self) -> u32 where u32: [c0nst] Answer {
+            1u32.answer()
+        }
+    }
+}
+
+fn main() {
+    let x = c0nst::c0nst! { 1 + 2 };
+    assert_eq!(x + W.get(), 4);
+}
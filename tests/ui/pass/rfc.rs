@@ -0,0 +1,27 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    pub c0nst trait Default {
+        fn default() -> Self;
+    }
+
+    impl c0nst Default for () {
+        fn default() -> Self {}
+    }
+
+    pub struct Thing<T>(pub T);
+
+    impl<T: [c0nst] Default> c0nst Default for Thing<T> {
+        fn default() -> Self {
+            Self(T::default())
+        }
+    }
+
+    pub c0nst fn default<T: [c0nst] Default>() -> T {
+        T::default()
+    }
+}
+
+fn main() {
+    let Thing(()) = default::<Thing<()>>();
+}
@@ -0,0 +1,42 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    c0nst::select! {
+        nightly => {
+            pub const TARGET: &str = "nightly";
+        },
+        stable => {
+            pub const TARGET: &str = "stable";
+        },
+    }
+
+    pub c0nst fn sum(values: &[u32]) -> u32 {
+        c0nst::select! {
+            nightly => {
+                let mut total = 0;
+                let mut i = 0;
+                while i < values.len() {
+                    total += values[i];
+                    i += 1;
+                }
+                total
+            },
+            stable => { values.iter().sum() },
+        }
+    }
+
+    pub c0nst fn first(values: &[u32]) -> u32 {
+        c0nst::select! {
+            nightly => { let first = if values.is_empty() { 0 } else { values[0] }; },
+            stable => { let first = values.first().copied().unwrap_or(0); },
+        }
+        first
+    }
+}
+
+fn main() {
+    assert_eq!(sum(&[1, 2, 3]), 6);
+    assert_eq!(first(&[4, 5]), 4);
+    let expected = if cfg!(feature = "nightly") { "nightly" } else { "stable" };
+    assert_eq!(TARGET, expected);
+}
@@ -0,0 +1,23 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    #![c0nst(stable_const)]
+
+    pub c0nst fn square(x: u32) -> u32 {
+        x * x
+    }
+
+    pub struct Meters(pub u32);
+
+    impl Meters {
+        pub c0nst fn area(&self) -> u32 {
+            square(self.0)
+        }
+    }
+}
+
+const AREA: u32 = Meters(4).area();
+
+fn main() {
+    assert_eq!(AREA, 16);
+}
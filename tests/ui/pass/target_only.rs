@@ -0,0 +1,41 @@
+#![cfg_attr(feature = "nightly", feature(const_trait_impl))]
+
+c0nst::c0nst! {
+    pub c0nst trait Answer {
+        fn answer() -> u8;
+    }
+
+    pub struct Deep;
+
+    #[c0nst(nightly_only)]
+    impl c0nst Answer for Deep {
+        fn answer() -> u8 {
+            42
+        }
+    }
+
+    #[c0nst(stable_only)]
+    impl Answer for Deep {
+        fn answer() -> u8 {
+            42
+        }
+    }
+
+    impl c0nst Deep {
+        #[c0nst(nightly_only)]
+        fn target() -> &'static str {
+            "nightly"
+        }
+
+        #[c0nst(stable_only)]
+        fn target() -> &'static str {
+            "stable"
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(Deep::answer(), 42);
+    let expected = if cfg!(feature = "nightly") { "nightly" } else { "stable" };
+    assert_eq!(Deep::target(), expected);
+}
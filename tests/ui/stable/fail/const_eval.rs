@@ -0,0 +1,16 @@
+// On stable the trait is not const, so it cannot be used in a constant.
+c0nst::c0nst! {
+    pub c0nst trait Compute {
+        fn calculate(&self) -> u32;
+    }
+
+    impl c0nst Compute for u32 {
+        fn calculate(&self) -> u32 {
+            *self * 2
+        }
+    }
+}
+
+const VALUE: u32 = 21u32.calculate();
+
+fn main() {}
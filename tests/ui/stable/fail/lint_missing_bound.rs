@@ -0,0 +1,13 @@
+#![deny(deprecated)]
+
+c0nst::c0nst! {
+    #![c0nst(lint)]
+
+    pub c0nst fn make<T: Default>() -> T {
+        T::default()
+    }
+}
+
+fn main() {
+    let _: u32 = make();
+}
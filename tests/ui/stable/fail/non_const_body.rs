@@ -0,0 +1,17 @@
+#![deny(deprecated)]
+
+c0nst::c0nst! {
+    #![c0nst(lint)]
+
+    pub c0nst fn sum(values: &[u32]) -> u32 {
+        let mut total = 0;
+        for value in values {
+            total += value;
+        }
+        total
+    }
+}
+
+fn main() {
+    assert_eq!(sum(&[1, 2]), 3);
+}
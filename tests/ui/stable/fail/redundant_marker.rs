@@ -0,0 +1,17 @@
+#![deny(deprecated)]
+
+c0nst::c0nst! {
+    pub c0nst trait Answer {
+        c0nst fn answer(&self) -> u32;
+    }
+
+    impl c0nst Answer for u32 {
+        fn answer(&self) -> u32 {
+            *self
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(42u32.answer(), 42);
+}